@@ -0,0 +1,20 @@
+//! examples/idle_shared_access_only.rs
+
+#[mock::app(idle_shared_access_only)]
+mod app {
+    #[resources]
+    struct Resources {
+        #[init(0)]
+        x: u32,
+        #[init(0)]
+        y: u32,
+    }
+
+    #[idle(resources = [&x])]
+    fn idle(_: idle::Context) -> ! {
+        loop {}
+    }
+
+    #[task(resources = [y])]
+    fn foo(_: foo::Context) {}
+}
@@ -0,0 +1,32 @@
+//! examples/split_attributes.rs
+
+#[mock::app(parse_binds)]
+mod app {
+    #[resources]
+    struct Resources {
+        #[init(0)]
+        x: u32,
+        #[init(0)]
+        y: u32,
+    }
+
+    #[init]
+    #[init(resources = [x])]
+    fn init(_: init::Context) -> (init::LateResources, init::Monotonics) {
+        init::LateResources {}
+    }
+
+    #[idle(resources = [x])]
+    #[idle()]
+    fn idle(_: idle::Context) -> ! {
+        loop {}
+    }
+
+    #[task(resources = [y])]
+    #[task(capacity = 2, priority = 2)]
+    fn foo(_: foo::Context) {}
+
+    #[task(binds = UART0)]
+    #[task(priority = 3, resources = [y])]
+    fn bar(_: bar::Context) {}
+}
@@ -10,19 +10,66 @@ use rtic_syntax::Settings;
 
 #[proc_macro_attribute]
 pub fn app(args: TokenStream, input: TokenStream) -> TokenStream {
+    // parser flags like `parse_binds` are accepted in the attribute
+    expand(args, input, Settings::default())
+}
+
+#[proc_macro_attribute]
+pub fn app_strict(args: TokenStream, input: TokenStream) -> TokenStream {
+    // like a backend that controls the parser flags itself
     let mut settings = Settings::default();
-    let mut rtic_args = vec![];
-    for arg in args.to_string().split(',') {
-        if arg.trim() == "parse_binds" {
-            settings.parse_binds = true;
-        } else if arg.trim() == "parse_extern_interrupt" {
-            settings.parse_extern_interrupt = true;
-        } else {
-            rtic_args.push(arg.to_string());
-        }
-    }
+    settings.accept_parser_flags_in_attribute = false;
+
+    expand(args, input, settings)
+}
+
+#[proc_macro_attribute]
+pub fn app_reserved(args: TokenStream, input: TokenStream) -> TokenStream {
+    // like a backend that leaves priority 2 to an interrupt handler outside the application
+    let mut settings = Settings::default();
+    settings.reserved_priorities.insert(2);
+
+    expand(args, input, settings)
+}
+
+#[proc_macro_attribute]
+pub fn app_idle_shared(args: TokenStream, input: TokenStream) -> TokenStream {
+    // like a backend that only gives `idle` shared access to resources
+    let mut settings = Settings::default();
+    settings.idle_shared_access_only = true;
+
+    expand(args, input, settings)
+}
+
+#[proc_macro_attribute]
+pub fn app_core_peripherals(args: TokenStream, input: TokenStream) -> TokenStream {
+    // like a backend that lets `init` or `idle` take the core peripherals
+    let mut settings = Settings::default();
+    settings.parse_core_peripherals = true;
+
+    expand(args, input, settings)
+}
+
+#[proc_macro_attribute]
+pub fn app_snake_case(args: TokenStream, input: TokenStream) -> TokenStream {
+    // like a backend that enforces `snake_case` task and resource names
+    let mut settings = Settings::default();
+    settings.enforce_snake_case_tasks = true;
+
+    expand(args, input, settings)
+}
+
+#[proc_macro_attribute]
+pub fn app_read_only_late(args: TokenStream, input: TokenStream) -> TokenStream {
+    // like a backend that places read-only resources in read-only memory
+    let mut settings = Settings::default();
+    settings.reject_read_only_late_resources = true;
+
+    expand(args, input, settings)
+}
 
-    if let Err(e) = rtic_syntax::parse(rtic_args.join(", ").parse().unwrap(), input, settings) {
+fn expand(args: TokenStream, input: TokenStream, settings: Settings) -> TokenStream {
+    if let Err(e) = rtic_syntax::parse(args.to_string().parse().unwrap(), input, settings) {
         e.to_compile_error().into()
     } else {
         "fn main() {}".parse().unwrap()
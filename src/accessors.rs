@@ -1,8 +1,10 @@
+use quote::format_ident;
 use syn::{Expr, Ident};
 
 use crate::{
     analyze::{Analysis, Location, Priority},
-    ast::{Access, App, LateResource},
+    ast::{Access, App, AppParts, LateResource, LateResourceDecl},
+    Context,
 };
 
 impl App {
@@ -30,34 +32,147 @@ impl App {
         })
     }
 
+    /// Returns an iterator over the declared late resources, in declaration order
+    ///
+    /// Which of them `init` initializes is decided by the analysis; see
+    /// `Analysis::late_resources_initialized_by_init`
+    ///
+    /// ```
+    /// use rtic_syntax::ast::App;
+    ///
+    /// fn late_resource_names(app: &App) -> Vec<String> {
+    ///     app.declared_late_resources()
+    ///         .map(|late| late.name.to_string())
+    ///         .collect()
+    /// }
+    /// ```
+    pub fn declared_late_resources(&self) -> impl Iterator<Item = LateResourceDecl<'_>> {
+        self.late_resources
+            .iter()
+            .map(|(name, late)| LateResourceDecl {
+                name,
+                ty: &late.ty,
+                cfgs: &late.cfgs,
+            })
+    }
+
+    /// Moves the contents of the `App` out of it, without cloning them
+    ///
+    /// Meant for backends that take ownership of the tasks and resources when generating code;
+    /// the fields of `App` remain available for borrowing
+    pub fn into_parts(self) -> AppParts {
+        AppParts {
+            args: self.args,
+            name: self.name,
+            inits: self.inits,
+            idles: self.idles,
+            monotonics: self.monotonics,
+            late_resources: self.late_resources,
+            resources: self.resources,
+            user_imports: self.user_imports,
+            user_code: self.user_code,
+            user_types: self.user_types,
+            hardware_tasks: self.hardware_tasks,
+            software_tasks: self.software_tasks,
+        }
+    }
+
+    /// Puts the `App` in a canonical form
+    ///
+    /// Resource declarations, tasks, monotonics, every context's `resources` / `late` lists, the
+    /// `shares_line_with` lists and the `arg_order` of every set of arguments are sorted by name.
+    /// Arguments that have a default value (`priority`, `capacity`, `default`) are added to
+    /// `arg_order` when they were omitted and `priority_explicit` is set, so that `#[task]` and
+    /// `#[task(priority = 1)]` are indistinguishable.
+    ///
+    /// Two applications that only differ in the order in which these items were written or in
+    /// spelling out default values end up with identical ASTs. The order of the `dispatchers` list
+    /// is preserved as it is semantically meaningful.
+    pub fn canonicalize(&mut self) {
+        // adds the omitted arguments that have a default value, then sorts the arguments
+        fn fill_defaults(arg_order: &mut Vec<Ident>, defaults: &[&str]) {
+            for default in defaults {
+                if !arg_order.iter().any(|arg| arg == default) {
+                    arg_order.push(format_ident!("{}", default));
+                }
+            }
+
+            arg_order.sort();
+        }
+
+        self.late_resources.sort_keys();
+        self.resources.sort_keys();
+        self.monotonics.sort_keys();
+        self.hardware_tasks.sort_keys();
+        self.software_tasks.sort_keys();
+
+        self.args.arg_order.sort();
+
+        for init in &mut self.inits {
+            init.args.late.sort();
+            init.args.resources.sort_keys();
+            init.args.arg_order.sort();
+        }
+
+        for idle in &mut self.idles {
+            idle.args.resources.sort_keys();
+            idle.args.arg_order.sort();
+        }
+
+        for monotonic in self.monotonics.values_mut() {
+            fill_defaults(&mut monotonic.args.arg_order, &["default", "priority"]);
+        }
+
+        for task in self.hardware_tasks.values_mut() {
+            task.args.priority_explicit = true;
+            task.args.resources.sort_keys();
+            task.args.shares_line_with.sort();
+            fill_defaults(&mut task.args.arg_order, &["priority"]);
+        }
+
+        for task in self.software_tasks.values_mut() {
+            task.args.priority_explicit = true;
+            task.args.resources.sort_keys();
+            fill_defaults(&mut task.args.arg_order, &["capacity", "priority"]);
+        }
+    }
+
+    /// Removes the resources that no context accesses
+    ///
+    /// `analysis` must be the analysis of this application: a resource is dead if it's missing
+    /// from `Analysis.locations`. Dead late resources are also removed from `init`'s `late` list.
+    /// Software tasks are never removed as any context may spawn them
+    pub fn prune_dead(&mut self, analysis: &Analysis) {
+        let live = |name: &Ident| analysis.locations.contains_key(name);
+
+        self.resources.retain(|name, _| live(name));
+        self.late_resources.retain(|name, _| live(name));
+
+        for init in &mut self.inits {
+            init.args.late.retain(|name| live(name));
+        }
+    }
+
+    // All the contexts, in declaration order: `init`, `idle`, hardware tasks, software tasks
+    pub(crate) fn contexts(&self) -> impl Iterator<Item = Context<'_>> {
+        self.inits
+            .iter()
+            .map(|_| Context::Init)
+            .chain(self.idles.iter().map(|_| Context::Idle))
+            .chain(self.hardware_tasks.keys().map(Context::HardwareTask))
+            .chain(self.software_tasks.keys().map(Context::SoftwareTask))
+    }
+
     pub(crate) fn resource_accesses(
         &self,
     ) -> impl Iterator<Item = (Option<Priority>, &Ident, Access)> {
-        self.inits
-            .iter()
-            .flat_map(|init| {
-                init.args
-                    .resources
-                    .iter()
-                    .map(move |(name, access)| (None, name, *access))
-            })
-            .chain(self.idles.iter().flat_map(|idle| {
-                idle.args
-                    .resources
-                    .iter()
-                    .map(move |(name, access)| (Some(0), name, *access))
-            }))
-            .chain(self.hardware_tasks.values().flat_map(|task| {
-                task.args
-                    .resources
-                    .iter()
-                    .map(move |(name, access)| (Some(task.args.priority), name, *access))
-            }))
-            .chain(self.software_tasks.values().flat_map(|task| {
-                task.args
-                    .resources
-                    .iter()
-                    .map(move |(name, access)| (Some(task.args.priority), name, *access))
-            }))
+        self.contexts().flat_map(move |context| {
+            let priority = context.effective_priority(self).priority();
+
+            context
+                .resources(self)
+                .iter()
+                .map(move |(name, access)| (priority, name, *access))
+        })
     }
 }
@@ -1,36 +1,34 @@
 //! RTIC application analysis
 
-use core::cmp;
-use std::collections::{BTreeMap, BTreeSet, HashMap};
+use core::{cmp, fmt};
+use std::{
+    collections::{BTreeMap, BTreeSet, HashMap},
+    io,
+};
 
 use indexmap::IndexMap;
-use quote::format_ident;
-use syn::{Ident, Type};
+use proc_macro2::Span;
+use quote::{format_ident, quote};
+use syn::{parse, spanned::Spanned, Attribute, Ident, PathArguments, Type};
 
-use crate::{ast::App, Set};
+use crate::{
+    ast::{Access, App, LateResourceDecl},
+    Context, Map, Set, Settings,
+};
 
-pub(crate) fn app(app: &App) -> Result<Analysis, syn::Error> {
+pub(crate) fn app(app: &App, settings: &Settings) -> Result<Analysis, syn::Error> {
     // a. Initialization of resources
-    let mut late_resources = LateResources::new();
+    let mut late_resource_assignment = LateResources::new();
     if !app.late_resources.is_empty() {
-        let mut resources = app.late_resources.keys().cloned().collect::<BTreeSet<_>>();
-        let mut rest = false;
         if let Some(init) = &app.inits.first() {
             if init.args.late.is_empty() {
-                rest = true;
+                // `init` initializes all the late resources
+                late_resource_assignment.push(app.late_resources.keys().cloned().collect());
             } else {
-                let mut late_resources = Vec::new();
-
-                for name in &init.args.late {
-                    late_resources.push(name.clone());
-                    resources.remove(name);
-                }
+                // `check` made sure that this list covers all the late resources
+                late_resource_assignment.push(init.args.late.iter().cloned().collect());
             }
         }
-
-        if rest {
-            late_resources.push(resources);
-        }
     }
 
     // Collect task local resources
@@ -92,8 +90,14 @@ pub(crate) fn app(app: &App) -> Result<Analysis, syn::Error> {
         }))
         .collect();
 
-    // Create the list of task Idents
-    let tasks = task_list.iter().map(|x| format_ident!("{}", x.0)).collect();
+    // Create the list of task Idents; tasks with disjoint `#[cfg]`s may share a name
+    let mut tasks: Tasks = vec![];
+    for (name, _, _) in &task_list {
+        let name = format_ident!("{}", name);
+        if !tasks.contains(&name) {
+            tasks.push(name);
+        }
+    }
 
     // Check that task_local resources are only used once
     let mut error = vec![];
@@ -193,6 +197,7 @@ pub(crate) fn app(app: &App) -> Result<Analysis, syn::Error> {
 
     let mut ownerships = Ownerships::new();
     let mut sync_types = SyncTypes::new();
+    let mut sync_type_origins = TypeOrigins::new();
     for (prio, name, access) in app.resource_accesses() {
         let res = app.resource(name).expect("UNREACHABLE").0;
 
@@ -214,7 +219,13 @@ pub(crate) fn app(app: &App) -> Result<Analysis, syn::Error> {
                         };
 
                         if access.is_shared() {
-                            sync_types.insert(res.ty.clone());
+                            require(
+                                settings,
+                                &mut sync_types,
+                                &mut sync_type_origins,
+                                &res.ty,
+                                TypeOrigin::Resource(name.clone()),
+                            );
                         }
                     }
 
@@ -230,8 +241,18 @@ pub(crate) fn app(app: &App) -> Result<Analysis, syn::Error> {
         }
     }
 
+    // Memory pools are used when spawning, even if no context lists them as a resource
+    for pool in app
+        .software_tasks
+        .values()
+        .filter_map(|task| task.args.pool.as_ref())
+    {
+        locations.entry(pool.clone()).or_insert(Location::Owned);
+    }
+
     // Most late resources need to be `Send`
     let mut send_types = SendTypes::new();
+    let mut send_type_origins = TypeOrigins::new();
     let owned_by_idle = Ownership::Owned { priority: 0 };
     for (name, res) in app.late_resources.iter() {
         // handle not owned by idle
@@ -240,7 +261,13 @@ pub(crate) fn app(app: &App) -> Result<Analysis, syn::Error> {
             .map(|ownership| *ownership != owned_by_idle)
             .unwrap_or(false)
         {
-            send_types.insert(res.ty.clone());
+            require(
+                settings,
+                &mut send_types,
+                &mut send_type_origins,
+                &res.ty,
+                TypeOrigin::Resource(name.clone()),
+            );
         }
     }
 
@@ -248,7 +275,13 @@ pub(crate) fn app(app: &App) -> Result<Analysis, syn::Error> {
     for name in app.inits.iter().flat_map(|init| init.args.resources.keys()) {
         if let Some(ownership) = ownerships.get(name) {
             if *ownership != owned_by_idle {
-                send_types.insert(app.resources[name].ty.clone());
+                require(
+                    settings,
+                    &mut send_types,
+                    &mut send_type_origins,
+                    &app.resources[name].ty,
+                    TypeOrigin::Resource(name.clone()),
+                );
             }
         }
     }
@@ -262,9 +295,22 @@ pub(crate) fn app(app: &App) -> Result<Analysis, syn::Error> {
         channel.tasks.insert(name.clone());
 
         // All inputs are now send as we do not know from where they may be spawned.
-        spawnee.inputs.iter().for_each(|input| {
-            send_types.insert(input.ty.clone());
-        });
+        spawnee
+            .inputs
+            .iter()
+            .enumerate()
+            .for_each(|(index, input)| {
+                require(
+                    settings,
+                    &mut send_types,
+                    &mut send_type_origins,
+                    &input.ty,
+                    TypeOrigin::TaskInput {
+                        task: name.clone(),
+                        index,
+                    },
+                );
+            });
     }
 
     // No channel should ever be empty
@@ -279,17 +325,377 @@ pub(crate) fn app(app: &App) -> Result<Analysis, syn::Error> {
             .sum();
     }
 
+    // Assign dispatchers: channels in ascending priority order get the extern interrupts in
+    // declaration order
+    let dispatchers: Dispatchers = channels
+        .keys()
+        .cloned()
+        .zip(app.args.extern_interrupts.keys().cloned())
+        .collect();
+    let dispatcher_bounds = dispatcher_bounds(app);
+
+    // The hardware tasks bound to each interrupt and the sources that share its line. Tasks with
+    // disjoint `#[cfg]`s may bind the same interrupt; they are merged in declaration order
+    let mut interrupts = Interrupts::new();
+    for (name, task) in &app.hardware_tasks {
+        let interrupt = interrupts
+            .entry(task.args.binds.clone())
+            .or_insert_with(|| InterruptUse {
+                tasks: vec![],
+                shares_line_with: vec![],
+            });
+
+        interrupt.tasks.push((name.clone(), task.cfgs.clone()));
+        for source in &task.args.shares_line_with {
+            if !interrupt.shares_line_with.contains(source) {
+                interrupt.shares_line_with.push(source.clone());
+            }
+        }
+    }
+
+    // Tasks that declared a latency budget, from the lowest priority to the highest
+    let mut latency_hints = app
+        .hardware_tasks
+        .iter()
+        .map(|(name, task)| (name, task.args.priority, task.args.latency))
+        .chain(
+            app.software_tasks
+                .iter()
+                .map(|(name, task)| (name, task.args.priority, task.args.latency)),
+        )
+        .filter_map(|(name, priority, latency)| {
+            latency.map(|latency| LatencyHint {
+                task: name.clone(),
+                priority,
+                latency,
+            })
+        })
+        .collect::<Vec<_>>();
+    latency_hints.sort_by_key(|hint| hint.priority);
+
+    // Tasks that access late resources must not run before `init` has initialized them
+    let accesses_late = |resources: &crate::ast::Resources| {
+        resources
+            .keys()
+            .any(|name| app.late_resources.contains_key(name))
+    };
+    let pre_init_masked_interrupts = app
+        .hardware_tasks
+        .values()
+        .filter(|task| accesses_late(&task.args.resources))
+        .map(|task| task.args.binds.clone())
+        .chain(
+            app.software_tasks
+                .values()
+                .filter(|task| accesses_late(&task.args.resources))
+                .filter_map(|task| dispatchers.get(&task.args.priority).cloned()),
+        )
+        .collect();
+
+    // The core peripherals go to `idle` if it asks for them; otherwise `init` keeps them
+    let core_peripherals_user = app
+        .idles
+        .iter()
+        .find(|idle| idle.args.core_peripherals)
+        .map(|idle| idle.name.clone())
+        .or_else(|| app.inits.first().map(|init| init.name.clone()));
+
+    // Which contexts access each resource, and how
+    let mut resource_users = ResourceUsers::new();
+    for context in app.contexts() {
+        let priority = context.effective_priority(app).priority();
+
+        for (name, access) in context.resources(app) {
+            resource_users
+                .entry(name.clone())
+                .or_default()
+                .push(ResourceUser {
+                    context: context.ident(app).clone(),
+                    priority,
+                    access: *access,
+                });
+        }
+    }
+
+    // Everything that runs at each priority level
+    let mut priority_usage = PriorityUsage::new();
+    for (name, task) in &app.hardware_tasks {
+        priority_usage
+            .entry(task.args.priority)
+            .or_default()
+            .hardware_tasks
+            .push(name.clone());
+    }
+    for (name, task) in &app.software_tasks {
+        priority_usage
+            .entry(task.args.priority)
+            .or_default()
+            .software_tasks
+            .push(name.clone());
+    }
+    for (priority, dispatcher) in &dispatchers {
+        priority_usage.entry(*priority).or_default().dispatcher = Some(dispatcher.clone());
+    }
+    for (name, monotonic) in &app.monotonics {
+        priority_usage
+            .entry(monotonic.args.priority)
+            .or_default()
+            .timer_queues
+            .push(name.clone());
+    }
+
+    // Resources that no context, `init` included, accesses exclusively
+    let read_only_resources = resource_users
+        .iter()
+        .filter(|(_, users)| users.iter().all(|user| user.access.is_shared()))
+        .map(|(name, _)| name.clone())
+        .collect();
+
+    // Shared accesses to these resources can be handed out as `&'static` references
+    let static_shared_resources = resource_users
+        .iter()
+        .filter(|(name, users)| {
+            // `init` runs before any other context so its accesses don't matter
+            let mut users = users.iter().filter(|user| user.priority.is_some());
+
+            users.clone().any(|user| user.access.is_shared())
+                && (ownerships
+                    .get(*name)
+                    .map(|ownership| !matches!(ownership, Ownership::Contended { .. }))
+                    .unwrap_or(false)
+                    || users.all(|user| user.access.is_shared()))
+        })
+        .map(|(name, _)| name.clone())
+        .collect();
+
+    // Fields of each context's `Context` struct. A hardware and a software task with disjoint
+    // `#[cfg]`s may share a name; their layouts are merged
+    let mut context_layouts = ContextLayouts::new();
+    for context in app.contexts() {
+        let name = context.ident(app);
+        let layout = context_layouts.entry(name.clone()).or_default();
+
+        layout.resources |= context.has_resources(app);
+        layout.locals |= context.has_locals(app);
+        layout.device_peripherals |= context.is_init() && app.args.peripherals;
+        layout.core_peripherals |= core_peripherals_user.as_ref() == Some(name);
+        layout.late_resources_return |= context.is_init();
+    }
+
+    // Without BASEPRI critical sections must mask all interrupts
+    let critical_section_strategy = if settings.no_basepri {
+        CsStrategy::MaskAll
+    } else {
+        CsStrategy::Basepri
+    };
+
+    // Every declared resource, whether it's accessed or not
+    let declared_resources = app
+        .resources
+        .keys()
+        .chain(app.late_resources.keys())
+        .cloned()
+        .collect();
+
+    // Generated code lists these types in this order; make it independent of how the
+    // application is written
+    let send_types = sort_types(send_types);
+    let sync_types = sort_types(sync_types);
+
     Ok(Analysis {
         channels,
-        late_resources,
+        context_layouts,
+        core_peripherals_user,
+        critical_section_strategy,
+        dispatchers,
+        dispatcher_bounds,
+        interrupts,
+        late_resource_assignment,
+        latency_hints,
         locations,
         tasks,
         ownerships,
+        pre_init_masked_interrupts,
+        priority_usage,
+        read_only_resources,
+        declared_resources,
+        resource_users,
         send_types,
+        send_type_origins,
+        static_shared_resources,
         sync_types,
+        sync_type_origins,
     })
 }
 
+// Records that `ty`, declared by `origin`, must implement a marker trait
+fn require(
+    settings: &Settings,
+    types: &mut Set<Box<Type>>,
+    origins: &mut TypeOrigins,
+    ty: &Type,
+    origin: TypeOrigin,
+) {
+    if settings.elide_trivial_trait_asserts && is_trivially_send_sync(ty) {
+        return;
+    }
+
+    types.insert(Box::new(ty.clone()));
+
+    let origins = origins.entry(Box::new(ty.clone())).or_default();
+    if !origins.contains(&origin) {
+        origins.push(origin);
+    }
+}
+
+// Number of dispatchers needed with and without the software tasks that have `#[cfg]`s
+pub(crate) fn dispatcher_bounds(app: &App) -> DispatcherBounds {
+    let levels = |all: bool| {
+        app.software_tasks
+            .values()
+            .filter(|task| all || task.cfgs.is_empty())
+            .map(|task| task.args.priority)
+            .collect::<BTreeSet<_>>()
+            .len()
+    };
+
+    DispatcherBounds {
+        lower: levels(false),
+        upper: levels(true),
+    }
+}
+
+// Whether `ty` implements `Send` and `Sync` no matter what the application defines: primitives,
+// function pointers, and shared references, arrays, slices and tuples of those
+fn is_trivially_send_sync(ty: &Type) -> bool {
+    const PRIMITIVES: &[&str] = &[
+        "bool", "char", "f32", "f64", "i8", "i16", "i32", "i64", "i128", "isize", "str", "u8",
+        "u16", "u32", "u64", "u128", "usize",
+    ];
+
+    match ty {
+        Type::BareFn(_) | Type::Never(_) => true,
+        Type::Path(ty) => {
+            ty.qself.is_none()
+                && ty.path.leading_colon.is_none()
+                && ty.path.segments.len() == 1
+                && ty.path.segments[0].arguments == PathArguments::None
+                && PRIMITIVES
+                    .iter()
+                    .any(|primitive| ty.path.segments[0].ident == primitive)
+        }
+        Type::Reference(ty) => ty.mutability.is_none() && is_trivially_send_sync(&ty.elem),
+        Type::Array(ty) => is_trivially_send_sync(&ty.elem),
+        Type::Slice(ty) => is_trivially_send_sync(&ty.elem),
+        Type::Tuple(ty) => ty.elems.iter().all(is_trivially_send_sync),
+        Type::Paren(ty) => is_trivially_send_sync(&ty.elem),
+        Type::Group(ty) => is_trivially_send_sync(&ty.elem),
+        _ => false,
+    }
+}
+
+// Sorts types by their token representation
+fn sort_types(types: Set<Box<Type>>) -> Set<Box<Type>> {
+    let mut types = types
+        .into_iter()
+        .map(|ty| (quote!(#ty).to_string(), ty))
+        .collect::<Vec<_>>();
+    types.sort_by(|a, b| a.0.cmp(&b.0));
+
+    types.into_iter().map(|(_, ty)| ty).collect()
+}
+
+/// Computes what changed between the analyses of two versions of an application
+///
+/// Resources are matched by name so a renamed resource shows up as a removal plus an addition
+pub fn diff(old: &Analysis, new: &Analysis) -> AnalysisDiff {
+    let mut changes = vec![];
+
+    let resources = old.ownerships.keys().chain(
+        new.ownerships
+            .keys()
+            .filter(|name| !old.ownerships.contains_key(*name)),
+    );
+    for name in resources {
+        let (old, new) = (old.ownerships.get(name), new.ownerships.get(name));
+
+        if old != new {
+            changes.push(Change::Ownership {
+                resource: name.clone(),
+                old: old.cloned(),
+                new: new.cloned(),
+            });
+        }
+    }
+
+    let priorities = old
+        .channels
+        .keys()
+        .chain(new.channels.keys())
+        .collect::<BTreeSet<_>>();
+    for priority in priorities {
+        let capacity = |analysis: &Analysis| {
+            analysis
+                .channels
+                .get(priority)
+                .map(|channel| channel.capacity)
+                .unwrap_or(0)
+        };
+        let (old, new) = (capacity(old), capacity(new));
+
+        if old != new {
+            changes.push(Change::Capacity {
+                priority: *priority,
+                old,
+                new,
+            });
+        }
+    }
+
+    let priorities = old
+        .dispatchers
+        .keys()
+        .chain(new.dispatchers.keys())
+        .collect::<BTreeSet<_>>();
+    for priority in priorities {
+        let (old, new) = (old.dispatchers.get(priority), new.dispatchers.get(priority));
+
+        if old != new {
+            changes.push(Change::Dispatcher {
+                priority: *priority,
+                old: old.cloned(),
+                new: new.cloned(),
+            });
+        }
+    }
+
+    let types = |old: &Set<Box<Type>>, new: &Set<Box<Type>>, changes: &mut Vec<_>, sync| {
+        for ty in old.iter().filter(|ty| !new.contains(*ty)) {
+            let ty = quote!(#ty).to_string();
+
+            changes.push(if sync {
+                Change::SyncType { ty, added: false }
+            } else {
+                Change::SendType { ty, added: false }
+            });
+        }
+
+        for ty in new.iter().filter(|ty| !old.contains(*ty)) {
+            let ty = quote!(#ty).to_string();
+
+            changes.push(if sync {
+                Change::SyncType { ty, added: true }
+            } else {
+                Change::SendType { ty, added: true }
+            });
+        }
+    };
+    types(&old.send_types, &new.send_types, &mut changes, false);
+    types(&old.sync_types, &new.sync_types, &mut changes, true);
+
+    AnalysisDiff { changes }
+}
+
 /// Priority ceiling
 pub type Ceiling = Option<u8>;
 
@@ -308,10 +714,44 @@ pub type Tasks = Vec<Ident>;
 /// The result of analyzing an RTIC application
 pub struct Analysis {
     /// SPSC message channels
-    pub channels: Channels,
+    pub(crate) channels: Channels,
+
+    /// What goes in the `Context` struct of each context, keyed by the context's name
+    ///
+    /// Tasks with disjoint `#[cfg]`s that share a name share an entry: it has every field that
+    /// any of them needs
+    pub context_layouts: ContextLayouts,
+
+    /// The name of the context that takes the core peripherals
+    ///
+    /// This is `idle` if it requested them (`core_peripherals = true`) and `init` otherwise.
+    /// `None` means that no context takes them
+    pub core_peripherals_user: Option<Ident>,
 
-    /// The late resources
-    pub late_resources: LateResources,
+    /// How critical sections (resource locks) must be implemented
+    pub critical_section_strategy: CsStrategy,
+
+    /// The interrupts used to dispatch the software tasks of each priority level
+    pub(crate) dispatchers: Dispatchers,
+
+    /// How many dispatchers the software tasks need, depending on which `#[cfg]`s are enabled
+    pub dispatcher_bounds: DispatcherBounds,
+
+    /// The interrupts bound to hardware tasks, in declaration order
+    pub interrupts: Interrupts,
+
+    /// The late resources that `init` initializes
+    ///
+    /// This is what `init::LateResources` must contain, after expanding an omitted `late`
+    /// argument to all the late resources; use it instead of `InitArgs.late` for codegen. It's
+    /// empty when there are no late resources. See `late_resources_initialized_by_init` for the
+    /// declarations of these resources
+    pub late_resource_assignment: LateResources,
+
+    /// The tasks that declared a latency budget (`latency = ..`), sorted by priority
+    ///
+    /// Tasks of the same priority are in declaration order, hardware tasks first
+    pub latency_hints: Vec<LatencyHint>,
 
     /// Location of all *used* resources
     ///
@@ -322,21 +762,721 @@ pub struct Analysis {
     pub locations: Locations,
 
     /// A vector containing all task names
+    ///
+    /// Tasks with disjoint `#[cfg]`s that share a name are listed once
     pub tasks: Tasks,
 
     /// Resource ownership
-    pub ownerships: Ownerships,
+    pub(crate) ownerships: Ownerships,
+
+    /// Interrupts that must stay masked until `init` has initialized the late resources
+    ///
+    /// These are the interrupts bound to hardware tasks that access late resources and the
+    /// dispatchers of software tasks that do. All other interrupts may be unmasked before `init`
+    /// returns
+    pub pre_init_masked_interrupts: BTreeSet<Ident>,
+
+    /// What runs at each priority level
+    pub(crate) priority_usage: PriorityUsage,
+
+    /// Resources that are only ever accessed through shared references (`&x`), `init` included
+    ///
+    /// These resources never change after being initialized so a backend may place them in
+    /// read-only memory and skip locking altogether. They are always early resources
+    pub read_only_resources: BTreeSet<Resource>,
+
+    /// All resources, early ones first, in declaration order
+    pub(crate) declared_resources: Set<Resource>,
+
+    /// The contexts that access each resource, in declaration order (`init`, `idle`, hardware
+    /// tasks, software tasks)
+    pub resource_users: ResourceUsers,
 
     /// These types must implement the `Send` trait
     pub send_types: SendTypes,
 
+    /// The declarations that put each type in `send_types`
+    pub(crate) send_type_origins: TypeOrigins,
+
+    /// Resources whose shared (`&x`) accesses can use `&'static` references
+    ///
+    /// This is the case when no context can modify the resource while the reference is alive:
+    /// either the resource is not contended (`Owned` or `CoOwned`) or no context other than
+    /// `init` accesses it exclusively. Shared accesses to other resources must use references
+    /// scoped to a critical section
+    pub static_shared_resources: BTreeSet<Resource>,
+
     /// These types must implement the `Sync` trait
     pub sync_types: SyncTypes,
+
+    /// The declarations that put each type in `sync_types`
+    pub(crate) sync_type_origins: TypeOrigins,
+}
+
+impl Analysis {
+    /// SPSC message channels
+    ///
+    /// No channel is empty and the capacity of each channel is the sum of the capacities of its
+    /// tasks
+    ///
+    /// ```
+    /// fn capacity(analysis: &rtic_syntax::analyze::Analysis) -> u8 {
+    ///     analysis.channels().values().map(|channel| channel.capacity).sum()
+    /// }
+    /// ```
+    ///
+    /// The field itself is not public
+    ///
+    /// ```compile_fail
+    /// fn capacity(analysis: &rtic_syntax::analyze::Analysis) -> u8 {
+    ///     analysis.channels.values().map(|channel| channel.capacity).sum()
+    /// }
+    /// ```
+    pub fn channels(&self) -> &Channels {
+        &self.channels
+    }
+
+    /// Mutable access to the channels; the caller is responsible for upholding their invariants
+    #[doc(hidden)]
+    pub fn channels_mut(&mut self) -> &mut Channels {
+        &mut self.channels
+    }
+
+    /// The interrupts used to dispatch the software tasks of each priority level
+    ///
+    /// Every key is also a key of `channels`
+    ///
+    /// ```
+    /// fn dispatches_everything(analysis: &rtic_syntax::analyze::Analysis) -> bool {
+    ///     analysis.channels().len() == analysis.dispatchers().len()
+    /// }
+    /// ```
+    pub fn dispatchers(&self) -> &Dispatchers {
+        &self.dispatchers
+    }
+
+    /// Mutable access to the dispatchers; the caller is responsible for upholding their
+    /// invariants
+    #[doc(hidden)]
+    pub fn dispatchers_mut(&mut self) -> &mut Dispatchers {
+        &mut self.dispatchers
+    }
+
+    /// Resource ownership
+    ///
+    /// The ceiling of a `Contended` resource is never lower than the priority of any of the tasks
+    /// that access it
+    ///
+    /// ```
+    /// use rtic_syntax::analyze::Analysis;
+    ///
+    /// fn contended(analysis: &Analysis) -> usize {
+    ///     analysis.ownerships().values().filter(|o| !o.is_owned()).count()
+    /// }
+    /// ```
+    ///
+    /// The field itself is not public
+    ///
+    /// ```compile_fail
+    /// use rtic_syntax::analyze::Analysis;
+    ///
+    /// fn contended(analysis: &Analysis) -> usize {
+    ///     analysis.ownerships.values().filter(|o| !o.is_owned()).count()
+    /// }
+    /// ```
+    pub fn ownerships(&self) -> &Ownerships {
+        &self.ownerships
+    }
+
+    /// Mutable access to the resource ownerships; the caller is responsible for upholding their
+    /// invariants
+    #[doc(hidden)]
+    pub fn ownerships_mut(&mut self) -> &mut Ownerships {
+        &mut self.ownerships
+    }
+
+    /// Pairs of latency hints where the task with the higher priority has the looser budget
+    ///
+    /// Higher priority tasks are expected to have tighter latency budgets so each pair, `(lower,
+    /// higher)` by priority, is likely a mistake that tools may want to report
+    pub fn latency_inversions(&self) -> Vec<(&LatencyHint, &LatencyHint)> {
+        let mut inversions = vec![];
+
+        for (i, lower) in self.latency_hints.iter().enumerate() {
+            for higher in &self.latency_hints[i + 1..] {
+                if higher.priority > lower.priority && higher.latency > lower.latency {
+                    inversions.push((lower, higher));
+                }
+            }
+        }
+
+        inversions
+    }
+
+    /// The declarations that require `ty`, an element of `send_types`, to implement `Send`
+    ///
+    /// `send_types` keeps a single copy of each type; this lists every resource and task input
+    /// that has it so that a backend can report the requirement at each of them, see
+    /// `TypeOrigin::span`. Empty if `ty` is not in `send_types`
+    pub fn send_type_origins(&self, ty: &Type) -> &[TypeOrigin] {
+        self.send_type_origins
+            .get(ty)
+            .map(|origins| &origins[..])
+            .unwrap_or(&[])
+    }
+
+    /// The declarations that require `ty`, an element of `sync_types`, to implement `Sync`
+    ///
+    /// See `send_type_origins`
+    pub fn sync_type_origins(&self, ty: &Type) -> &[TypeOrigin] {
+        self.sync_type_origins
+            .get(ty)
+            .map(|origins| &origins[..])
+            .unwrap_or(&[])
+    }
+
+    /// Checks the invariants that backends rely on
+    ///
+    /// - no channel is empty and each channel's capacity is the sum of its tasks' capacities
+    /// - every task in a channel is a software task that runs at the channel's priority
+    /// - every dispatcher dispatches an existing channel
+    /// - at most one of the hardware tasks bound to an interrupt has no `#[cfg]`s
+    /// - every accessed resource has a location and an ownership consistent with the priorities
+    ///   of the contexts that access it
+    pub fn is_sound(&self, app: &App) -> Result<(), Vec<parse::Error>> {
+        let mut errors = vec![];
+
+        for (priority, channel) in &self.channels {
+            if channel.tasks.is_empty() {
+                errors.push(parse::Error::new(
+                    Span::call_site(),
+                    format!("the channel of priority {} is empty", priority),
+                ));
+            }
+
+            let mut capacity = 0u8;
+            for name in &channel.tasks {
+                if let Some(task) = app.software_tasks.get(name) {
+                    if task.args.priority != *priority {
+                        errors.push(parse::Error::new(
+                            name.span(),
+                            format!(
+                                "software task `{}` is dispatched at priority {} but runs at \
+                                 priority {}",
+                                name, priority, task.args.priority
+                            ),
+                        ));
+                    }
+
+                    capacity = capacity.saturating_add(task.args.capacity);
+                } else {
+                    errors.push(parse::Error::new(
+                        name.span(),
+                        format!(
+                            "the channel of priority {} dispatches an unknown task",
+                            priority
+                        ),
+                    ));
+                }
+            }
+
+            if channel.capacity != capacity {
+                errors.push(parse::Error::new(
+                    Span::call_site(),
+                    format!(
+                        "the channel of priority {} has capacity {} but its tasks need {}",
+                        priority, channel.capacity, capacity
+                    ),
+                ));
+            }
+        }
+
+        for (priority, interrupt) in &self.dispatchers {
+            if !self.channels.contains_key(priority) {
+                errors.push(parse::Error::new(
+                    interrupt.span(),
+                    format!("this dispatcher has no channel at priority {}", priority),
+                ));
+            }
+        }
+
+        for (interrupt, usage) in &self.interrupts {
+            let unconditional = usage
+                .tasks
+                .iter()
+                .filter(|(_, cfgs)| cfgs.is_empty())
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>();
+
+            if unconditional.len() > 1 {
+                errors.push(parse::Error::new(
+                    interrupt.span(),
+                    format!(
+                        "`{}` and `{}` are both bound to this interrupt and neither has \
+                         `#[cfg]`s",
+                        unconditional[0], unconditional[1]
+                    ),
+                ));
+            }
+        }
+
+        for (priority, name, _) in app.resource_accesses() {
+            if !self.locations.contains_key(name) {
+                errors.push(parse::Error::new(
+                    name.span(),
+                    "this resource is accessed but has no location",
+                ));
+            }
+
+            let priority = if let Some(priority) = priority {
+                priority
+            } else {
+                continue;
+            };
+
+            let consistent = match self.ownerships.get(name) {
+                Some(Ownership::Owned { priority: owner })
+                | Some(Ownership::CoOwned { priority: owner }) => *owner == priority,
+                Some(Ownership::Contended { ceiling }) => *ceiling >= priority,
+                None => false,
+            };
+
+            if !consistent {
+                errors.push(parse::Error::new(
+                    name.span(),
+                    format!(
+                        "the ownership of this resource is inconsistent with an access at \
+                         priority {}",
+                        priority
+                    ),
+                ));
+            }
+        }
+
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Returns the interrupt that dispatches the software tasks that run at `priority`
+    pub fn dispatcher_for(&self, priority: Priority) -> Option<&Ident> {
+        self.dispatchers.get(&priority)
+    }
+
+    /// Everything that runs at each priority level: tasks, dispatchers and timer queue handlers
+    ///
+    /// Only the priority levels that are in use are listed; `idle` (priority 0) is not included
+    pub fn priority_usage(&self) -> &PriorityUsage {
+        &self.priority_usage
+    }
+
+    /// The tasks, hardware and software, grouped by priority level
+    ///
+    /// Tasks of the same class run to completion without preempting each other so they can share
+    /// resources without locks. `idle` is not included
+    pub fn priority_classes(&self) -> BTreeMap<Priority, BTreeSet<Task>> {
+        self.priority_usage
+            .iter()
+            .map(|(priority, usage)| {
+                let tasks = usage
+                    .hardware_tasks
+                    .iter()
+                    .chain(&usage.software_tasks)
+                    .cloned()
+                    .collect::<BTreeSet<_>>();
+
+                (*priority, tasks)
+            })
+            .filter(|(_, tasks)| !tasks.is_empty())
+            .collect()
+    }
+
+    /// The late resources that `init` initializes
+    #[deprecated(note = "use the `late_resource_assignment` field")]
+    pub fn late_resources(&self) -> &LateResources {
+        &self.late_resource_assignment
+    }
+
+    /// The declarations of the late resources that `init` initializes
+    ///
+    /// `app` must be the application this analysis was computed from
+    ///
+    /// ```
+    /// use proc_macro2::TokenStream;
+    /// use quote::quote;
+    /// use rtic_syntax::{analyze::Analysis, ast::App};
+    ///
+    /// fn late_resources_struct(app: &App, analysis: &Analysis) -> TokenStream {
+    ///     let fields = analysis
+    ///         .late_resources_initialized_by_init(app)
+    ///         .map(|late| {
+    ///             let (cfgs, name, ty) = (late.cfgs, late.name, late.ty);
+    ///
+    ///             quote!(#(#cfgs)* pub #name: #ty)
+    ///         });
+    ///
+    ///     quote!(pub struct LateResources { #(#fields,)* })
+    /// }
+    /// ```
+    pub fn late_resources_initialized_by_init<'a>(
+        &'a self,
+        app: &'a App,
+    ) -> impl Iterator<Item = LateResourceDecl<'a>> {
+        app.declared_late_resources().filter(move |late| {
+            self.late_resource_assignment
+                .iter()
+                .any(|resources| resources.contains(late.name))
+        })
+    }
+
+    /// A table of which resources each context accesses, and how
+    pub fn access_matrix(&self) -> AccessMatrix {
+        let rows = self.context_layouts.keys().cloned().collect::<Vec<_>>();
+        let columns = self.declared_resources.iter().cloned().collect::<Vec<_>>();
+
+        let mut cells = vec![vec![None; columns.len()]; rows.len()];
+        for (resource, users) in &self.resource_users {
+            if let Some(column) = columns.iter().position(|name| name == resource) {
+                for user in users {
+                    if let Some(row) = rows.iter().position(|name| *name == user.context) {
+                        // contexts that share a name: exclusive access wins
+                        let cell = &mut cells[row][column];
+                        if cell.map(|access| access.is_shared()).unwrap_or(true) {
+                            *cell = Some(user.access);
+                        }
+                    }
+                }
+            }
+        }
+
+        AccessMatrix {
+            rows,
+            columns,
+            cells,
+        }
+    }
+
+    /// The application as a Stack Resource Policy (SRP) model, e.g. for schedulability analysis
+    ///
+    /// Priorities are SRP preemption levels and resource ceilings are the highest preemption
+    /// level of the contexts that access the resource. `init` runs before the scheduler starts so
+    /// it's not part of the model
+    pub fn srp_model(&self) -> SrpModel {
+        // A hardware and a software task with disjoint `#[cfg]`s may share a name and run at
+        // different priorities
+        let priorities_of = |name: &Ident| {
+            let priorities = self
+                .priority_usage
+                .iter()
+                .filter(|(_, usage)| {
+                    usage.hardware_tasks.contains(name) || usage.software_tasks.contains(name)
+                })
+                .map(|(priority, _)| *priority)
+                .collect::<Vec<_>>();
+
+            if priorities.is_empty() {
+                // `idle`
+                vec![0]
+            } else {
+                priorities
+            }
+        };
+
+        let tasks = self
+            .context_layouts
+            .iter()
+            .filter(|(_, layout)| !layout.late_resources_return)
+            .flat_map(|(name, _)| {
+                priorities_of(name)
+                    .into_iter()
+                    .map(move |priority| (name.clone(), priority))
+            })
+            .collect();
+
+        let resources = self
+            .ownerships
+            .iter()
+            .map(|(name, ownership)| {
+                let ceiling = match ownership {
+                    Ownership::Owned { priority } | Ownership::CoOwned { priority } => *priority,
+                    Ownership::Contended { ceiling } => *ceiling,
+                };
+
+                (name.clone(), ceiling)
+            })
+            .collect();
+
+        let mut locks = vec![];
+        for (resource, ownership) in &self.ownerships {
+            for user in self.resource_users.get(resource).into_iter().flatten() {
+                if let Some(priority) = user.priority {
+                    if ownership.needs_lock(priority) {
+                        locks.push((user.context.clone(), resource.clone()));
+                    }
+                }
+            }
+        }
+
+        SrpModel {
+            tasks,
+            resources,
+            locks,
+        }
+    }
+
+    /// Writes a human readable summary of the analysis of `app` to `w`
+    ///
+    /// The summary lists what runs at each priority level, explains the ceiling of each accessed
+    /// resource and ends with the `metrics`
+    pub fn write_summary<W: io::Write>(&self, w: &mut W, app: &App) -> io::Result<()> {
+        fn list(names: &[Ident]) -> String {
+            names
+                .iter()
+                .map(|name| format!("`{}`", name))
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+
+        for (priority, usage) in &self.priority_usage {
+            let mut parts = vec![];
+            if !usage.hardware_tasks.is_empty() {
+                parts.push(format!("hardware tasks {}", list(&usage.hardware_tasks)));
+            }
+            if !usage.software_tasks.is_empty() {
+                parts.push(format!("software tasks {}", list(&usage.software_tasks)));
+            }
+            if let Some(dispatcher) = &usage.dispatcher {
+                parts.push(format!("dispatcher `{}`", dispatcher));
+            }
+            if !usage.timer_queues.is_empty() {
+                parts.push(format!("timer queues {}", list(&usage.timer_queues)));
+            }
+
+            writeln!(w, "priority {}: {}", priority, parts.join("; "))?;
+        }
+
+        for name in self.ownerships.keys() {
+            if let (Some((resource, _)), Some(explanation)) =
+                (app.resource(name), self.explain_ceiling(name))
+            {
+                let ty = &resource.ty;
+                writeln!(w, "resource `{}: {}`: {}", name, quote!(#ty), explanation)?;
+            }
+        }
+
+        write!(w, "{}", self.metrics())
+    }
+
+    /// `write_summary` into a `String`
+    pub fn summary(&self, app: &App) -> String {
+        let mut summary = vec![];
+        self.write_summary(&mut summary, app)
+            .expect("writing to a `Vec` can't fail");
+
+        String::from_utf8(summary).expect("the summary is valid UTF-8")
+    }
+
+    /// Summary counts, e.g. to print in build logs or gate CI on
+    pub fn metrics(&self) -> Metrics {
+        let count =
+            |f: fn(&PriorityUse) -> usize| self.priority_usage.values().map(f).sum::<usize>();
+
+        Metrics {
+            resources: self.declared_resources.len(),
+            accessed_resources: self.ownerships.len(),
+            contended_resources: self
+                .ownerships
+                .values()
+                .filter(|ownership| matches!(ownership, Ownership::Contended { .. }))
+                .count(),
+            hardware_tasks: count(|usage| usage.hardware_tasks.len()),
+            software_tasks: count(|usage| usage.software_tasks.len()),
+            channels: self.channels.len(),
+            timer_queues: count(|usage| usage.timer_queues.len()),
+            send_types: self.send_types.len(),
+            sync_types: self.sync_types.len(),
+        }
+    }
+
+    /// The contexts that can write to `resource`, in declaration order
+    ///
+    /// These are the contexts with exclusive (`x`) access to it plus, for a late resource, `init`
+    /// which initializes it
+    pub fn writers_of(&self, resource: &Ident) -> Vec<&Ident> {
+        let initializer = if self
+            .late_resource_assignment
+            .iter()
+            .any(|resources| resources.contains(resource))
+        {
+            self.context_layouts
+                .iter()
+                .find(|(_, layout)| layout.late_resources_return)
+                .map(|(name, _)| name)
+        } else {
+            None
+        };
+
+        initializer
+            .into_iter()
+            .chain(self.users_of(resource, Access::is_exclusive))
+            .collect()
+    }
+
+    /// The contexts that can only read `resource`, i.e. that have shared (`&x`) access to it, in
+    /// declaration order
+    pub fn readers_of(&self, resource: &Ident) -> Vec<&Ident> {
+        self.users_of(resource, Access::is_shared).collect()
+    }
+
+    fn users_of<'a>(
+        &'a self,
+        resource: &Ident,
+        access: fn(&Access) -> bool,
+    ) -> impl Iterator<Item = &'a Ident> {
+        self.resource_users
+            .get(resource)
+            .into_iter()
+            .flatten()
+            .filter(move |user| access(&user.access))
+            .map(|user| &user.context)
+    }
+
+    /// Resources that a context may modify while a higher priority context can preempt it and
+    /// access them too, without a lock to prevent it
+    ///
+    /// A resource is a candidate when a context accesses it exclusively (`x`), a context of
+    /// higher priority also accesses it and its ownership is not `Contended` with a ceiling that
+    /// covers both. The analysis makes all such resources contended so this is expected to be
+    /// empty; it guards against regressions. `init` runs before any other context and is not
+    /// considered
+    ///
+    /// Each candidate comes with the contexts that access it, in declaration order
+    pub fn data_race_candidates<'a>(&self, app: &'a App) -> Vec<(Resource, Vec<Context<'a>>)> {
+        let mut accesses = Map::<Vec<(Context<'a>, Priority, Access)>>::new();
+        for context in app.contexts() {
+            let priority = match context.effective_priority(app).priority() {
+                Some(priority) => priority,
+                None => continue,
+            };
+
+            for (name, access) in context.resources(app) {
+                accesses
+                    .entry(name.clone())
+                    .or_default()
+                    .push((context, priority, *access));
+            }
+        }
+
+        let mut candidates = vec![];
+        for (name, accesses) in accesses {
+            let lowest_writer = accesses
+                .iter()
+                .filter(|(_, _, access)| access.is_exclusive())
+                .map(|(_, priority, _)| *priority)
+                .min();
+            let highest = accesses.iter().map(|(_, priority, _)| *priority).max();
+
+            // a higher priority context can preempt a writer
+            let highest = match (lowest_writer, highest) {
+                (Some(lowest_writer), Some(highest)) if highest > lowest_writer => highest,
+                _ => continue,
+            };
+
+            let locked = match self.ownerships.get(&name) {
+                Some(Ownership::Contended { ceiling }) => *ceiling >= highest,
+                _ => false,
+            };
+
+            if !locked {
+                candidates.push((
+                    name,
+                    accesses
+                        .into_iter()
+                        .map(|(context, _, _)| context)
+                        .collect(),
+                ));
+            }
+        }
+
+        candidates
+    }
+
+    /// Explains the ceiling of `resource` in terms of the contexts that access it
+    ///
+    /// e.g. "ceiling 5 because `bar` accesses it at priority 5 and `foo` at priority 2"
+    ///
+    /// Returns `None` if `resource` is not accessed by any task or `idle`
+    pub fn explain_ceiling(&self, resource: &Ident) -> Option<String> {
+        fn join(parts: &[String]) -> String {
+            match parts.split_last() {
+                Some((last, rest)) if !rest.is_empty() => {
+                    format!("{} and {}", rest.join(", "), last)
+                }
+                Some((last, _)) => last.clone(),
+                None => String::new(),
+            }
+        }
+
+        let ownership = self.ownerships.get(resource)?;
+
+        // `init` doesn't take part in the ceiling computation
+        let mut users = self
+            .resource_users
+            .get(resource)?
+            .iter()
+            .filter_map(|user| user.priority.map(|priority| (priority, &user.context)))
+            .collect::<Vec<_>>();
+        // highest priority first
+        users.sort_by(|a, b| b.0.cmp(&a.0));
+
+        let names = users
+            .iter()
+            .map(|(_, name)| format!("`{}`", name))
+            .collect::<Vec<_>>();
+
+        Some(match ownership {
+            Ownership::Contended { ceiling } => {
+                let parts = users
+                    .iter()
+                    .enumerate()
+                    .map(|(i, (priority, name))| {
+                        if i == 0 {
+                            format!("`{}` accesses it at priority {}", name, priority)
+                        } else {
+                            format!("`{}` at priority {}", name, priority)
+                        }
+                    })
+                    .collect::<Vec<_>>();
+
+                format!("ceiling {} because {}", ceiling, join(&parts))
+            }
+
+            Ownership::Owned { priority } => format!(
+                "no ceiling because only {} accesses it, at priority {}",
+                join(&names),
+                priority
+            ),
+
+            Ownership::CoOwned { priority } => format!(
+                "no ceiling because {} all access it at priority {}",
+                join(&names),
+                priority
+            ),
+        })
+    }
 }
 
 /// All channels, keyed by dispatch priority
 pub type Channels = BTreeMap<Priority, Channel>;
 
+/// Dispatcher interrupts, keyed by dispatch priority
+pub type Dispatchers = BTreeMap<Priority, Ident>;
+
+/// Layout of every context's `Context` struct, keyed by the context's name
+pub type ContextLayouts = IndexMap<Ident, ContextLayout>;
+
 /// Late resources, wrapped in a vector
 pub type LateResources = Vec<BTreeSet<Resource>>;
 
@@ -346,12 +1486,93 @@ pub type Locations = IndexMap<Resource, Location>;
 /// Resource ownership
 pub type Ownerships = IndexMap<Resource, Ownership>;
 
+/// What runs at each priority level
+pub type PriorityUsage = BTreeMap<Priority, PriorityUse>;
+
+/// The contexts that access each resource
+pub type ResourceUsers = IndexMap<Resource, Vec<ResourceUser>>;
+
 /// These types must implement the `Send` trait
+///
+/// The types are sorted by their token representation
 pub type SendTypes = Set<Box<Type>>;
 
 /// These types must implement the `Sync` trait
+///
+/// The types are sorted by their token representation
 pub type SyncTypes = Set<Box<Type>>;
 
+/// The interrupts bound to hardware tasks
+pub type Interrupts = Map<InterruptUse>;
+
+/// How the application uses an interrupt; see `Analysis.interrupts`
+#[derive(Clone, Debug, PartialEq)]
+pub struct InterruptUse {
+    /// The hardware tasks bound to the interrupt and their `#[cfg]`s, in declaration order
+    ///
+    /// There's more than one task only when their `#[cfg]`s are disjoint, so at most one of
+    /// them is compiled in
+    pub tasks: Vec<(Task, Vec<Attribute>)>,
+
+    /// The other interrupt sources wired to the same line, as declared with `shares_line_with`
+    ///
+    /// This is the union of the lists of all the tasks, in declaration order
+    pub shares_line_with: Vec<Ident>,
+}
+
+/// The latency budget of a task; see `Analysis.latency_hints`
+#[derive(Clone, Debug, PartialEq)]
+pub struct LatencyHint {
+    /// The task
+    pub task: Task,
+
+    /// The priority of the task
+    pub priority: Priority,
+
+    /// The declared latency budget
+    pub latency: u32,
+}
+
+/// A declaration whose type must implement `Send` or `Sync`; see `Analysis::send_type_origins`
+#[derive(Clone, Debug, PartialEq)]
+pub enum TypeOrigin {
+    /// The type of this resource, as written in the `#[resources]` struct
+    Resource(Ident),
+
+    /// The type of an input of a software task
+    TaskInput {
+        /// The software task
+        task: Ident,
+        /// The position of the input, not counting the context
+        index: usize,
+    },
+}
+
+impl TypeOrigin {
+    /// The span of the type as the user wrote it
+    ///
+    /// Backends can use this span to point unsatisfied `Send` / `Sync` requirements at the
+    /// declaration that introduced them
+    pub fn span(&self, app: &App) -> Span {
+        match self {
+            TypeOrigin::Resource(name) => app
+                .resource(name)
+                .map(|(res, _)| res.ty.span())
+                .unwrap_or_else(|| name.span()),
+
+            TypeOrigin::TaskInput { task, index } => app
+                .software_tasks
+                .get(task)
+                .and_then(|task| task.inputs.get(*index))
+                .map(|input| input.ty.span())
+                .unwrap_or_else(|| task.span()),
+        }
+    }
+}
+
+// The origins of each `Send` / `Sync` type, in the order they were found
+pub(crate) type TypeOrigins = HashMap<Box<Type>, Vec<TypeOrigin>>;
+
 /// A channel used to send messages
 #[derive(Debug, Default)]
 pub struct Channel {
@@ -362,6 +1583,64 @@ pub struct Channel {
     pub tasks: BTreeSet<Task>,
 }
 
+/// The fields that the `Context` struct of a context needs
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct ContextLayout {
+    /// A `resources` field: the context has a non-empty resource list
+    pub resources: bool,
+
+    /// A `locals` field: the context has local `static mut` variables
+    pub locals: bool,
+
+    /// A `device` field: the device specific peripherals (only `init` when `peripherals = true`)
+    pub device_peripherals: bool,
+
+    /// A `core` field: the core peripherals (see `Analysis.core_peripherals_user`)
+    pub core_peripherals: bool,
+
+    /// The context returns the `LateResources` struct (only `init`)
+    pub late_resources_return: bool,
+}
+
+/// How a resource lock raises the priority of the running context
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum CsStrategy {
+    /// Raise the priority to the ceiling using the BASEPRI register
+    Basepri,
+
+    /// Mask all interrupts for the duration of the critical section
+    MaskAll,
+}
+
+/// Everything that runs at one priority level
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct PriorityUse {
+    /// Hardware tasks, in declaration order
+    pub hardware_tasks: Vec<Task>,
+
+    /// Software tasks, in declaration order
+    pub software_tasks: Vec<Task>,
+
+    /// The interrupt that dispatches the software tasks of this level
+    pub dispatcher: Option<Ident>,
+
+    /// The monotonics whose timer queue handler runs at this level
+    pub timer_queues: Vec<Ident>,
+}
+
+/// A context that accesses a resource
+#[derive(Clone, Debug, PartialEq)]
+pub struct ResourceUser {
+    /// The name of the context
+    pub context: Ident,
+
+    /// The priority of the context; `None` for `init`, which runs before the scheduler starts
+    pub priority: Option<Priority>,
+
+    /// How the context accesses the resource
+    pub access: Access,
+}
+
 /// Resource ownership
 #[derive(Clone, Copy, Debug, PartialEq)]
 pub enum Ownership {
@@ -410,3 +1689,243 @@ pub enum Location {
     /// resource that is owned
     Owned,
 }
+
+/// The changes between the analyses of two versions of an application; see `diff`
+///
+/// The `Display` implementation renders one change per line
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct AnalysisDiff {
+    /// The changes, grouped by kind
+    pub changes: Vec<Change>,
+}
+
+impl AnalysisDiff {
+    /// Whether nothing changed
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+}
+
+impl fmt::Display for AnalysisDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for change in &self.changes {
+            writeln!(f, "{}", change)?;
+        }
+
+        Ok(())
+    }
+}
+
+/// The number of dispatchers that the software tasks need; see `Analysis::dispatcher_bounds`
+///
+/// Software tasks need one dispatcher per priority level. Whether a task behind a `#[cfg]` is
+/// compiled in is only known after expansion so both extremes are reported
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DispatcherBounds {
+    /// Dispatchers needed when every software task that has a `#[cfg]` is disabled
+    pub lower: usize,
+    /// Dispatchers needed when every software task is enabled
+    pub upper: usize,
+}
+
+/// Which resources each context accesses, and how; see `Analysis::access_matrix`
+#[derive(Clone, Debug, PartialEq)]
+pub struct AccessMatrix {
+    /// The contexts, in declaration order: `init`, `idle`, hardware tasks, software tasks
+    ///
+    /// Tasks with disjoint `#[cfg]`s that share a name share a row, where exclusive accesses
+    /// take precedence over shared ones
+    pub rows: Vec<Ident>,
+
+    /// The resources, in declaration order: early resources first, then late resources
+    pub columns: Vec<Resource>,
+
+    // `cells[row][column]`
+    cells: Vec<Vec<Option<Access>>>,
+}
+
+impl AccessMatrix {
+    /// How `context` accesses `resource`; `None` if it doesn't
+    pub fn get(&self, context: &Ident, resource: &Ident) -> Option<Access> {
+        let row = self.rows.iter().position(|name| name == context)?;
+        let column = self.columns.iter().position(|name| name == resource)?;
+
+        self.cells[row][column]
+    }
+}
+
+/// A Stack Resource Policy (SRP) model of an application; see `Analysis::srp_model`
+#[derive(Clone, Debug, PartialEq)]
+pub struct SrpModel {
+    /// `idle` and the tasks, in declaration order, with their preemption level
+    ///
+    /// Tasks with disjoint `#[cfg]`s that share a name are listed once per preemption level,
+    /// from the lowest to the highest
+    pub tasks: Vec<(Task, Priority)>,
+
+    /// The accessed resources, in the order of `Analysis::ownerships`, with their ceiling
+    pub resources: Vec<(Resource, Priority)>,
+
+    /// The `(task, resource)` pairs where the task must lock the resource: the task's
+    /// preemption level is below the resource's ceiling
+    pub locks: Vec<(Task, Resource)>,
+}
+
+/// Summary counts of an analysis
+///
+/// The `Display` implementation prints one `key=value` line per field, in declaration order
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Metrics {
+    /// Early and late resources, accessed or not
+    pub resources: usize,
+
+    /// Resources accessed by at least one context other than `init`
+    pub accessed_resources: usize,
+
+    /// Resources that need a lock
+    pub contended_resources: usize,
+
+    /// Hardware tasks
+    pub hardware_tasks: usize,
+
+    /// Software tasks
+    pub software_tasks: usize,
+
+    /// Message channels, one per software task priority level
+    pub channels: usize,
+
+    /// Timer queues, one per monotonic
+    pub timer_queues: usize,
+
+    /// Types that must implement `Send`
+    pub send_types: usize,
+
+    /// Types that must implement `Sync`
+    pub sync_types: usize,
+}
+
+impl fmt::Display for Metrics {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        writeln!(f, "resources={}", self.resources)?;
+        writeln!(f, "accessed_resources={}", self.accessed_resources)?;
+        writeln!(f, "contended_resources={}", self.contended_resources)?;
+        writeln!(f, "hardware_tasks={}", self.hardware_tasks)?;
+        writeln!(f, "software_tasks={}", self.software_tasks)?;
+        writeln!(f, "channels={}", self.channels)?;
+        writeln!(f, "timer_queues={}", self.timer_queues)?;
+        writeln!(f, "send_types={}", self.send_types)?;
+        writeln!(f, "sync_types={}", self.sync_types)
+    }
+}
+
+/// A single change between two analyses
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    /// The ownership, and thus the ceiling, of a resource changed
+    ///
+    /// `None` means that the resource is not accessed
+    Ownership {
+        /// The resource
+        resource: Resource,
+        /// Old ownership
+        old: Option<Ownership>,
+        /// New ownership
+        new: Option<Ownership>,
+    },
+
+    /// The capacity of the channel of a priority level changed
+    ///
+    /// `0` means that there's no channel
+    Capacity {
+        /// Dispatch priority
+        priority: Priority,
+        /// Old capacity
+        old: u8,
+        /// New capacity
+        new: u8,
+    },
+
+    /// The dispatcher of a priority level changed
+    ///
+    /// `None` means that the priority level has no dispatcher
+    Dispatcher {
+        /// Dispatch priority
+        priority: Priority,
+        /// Old dispatcher
+        old: Option<Ident>,
+        /// New dispatcher
+        new: Option<Ident>,
+    },
+
+    /// A type was added to or removed from the types that must implement `Send`
+    SendType {
+        /// The type, rendered as tokens
+        ty: String,
+        /// `true` if the type must now implement `Send`
+        added: bool,
+    },
+
+    /// A type was added to or removed from the types that must implement `Sync`
+    SyncType {
+        /// The type, rendered as tokens
+        ty: String,
+        /// `true` if the type must now implement `Sync`
+        added: bool,
+    },
+}
+
+impl fmt::Display for Change {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fn ownership(ownership: &Option<Ownership>) -> String {
+            match ownership {
+                None => "not accessed".to_string(),
+                Some(Ownership::Owned { priority }) => format!("owned at priority {}", priority),
+                Some(Ownership::CoOwned { priority }) => {
+                    format!("co-owned at priority {}", priority)
+                }
+                Some(Ownership::Contended { ceiling }) => format!("ceiling {}", ceiling),
+            }
+        }
+
+        fn dispatcher(dispatcher: &Option<Ident>) -> String {
+            dispatcher
+                .as_ref()
+                .map(|interrupt| format!("`{}`", interrupt))
+                .unwrap_or_else(|| "none".to_string())
+        }
+
+        match self {
+            Change::Ownership { resource, old, new } => write!(
+                f,
+                "resource `{}`: {} -> {}",
+                resource,
+                ownership(old),
+                ownership(new)
+            ),
+
+            Change::Capacity { priority, old, new } => write!(
+                f,
+                "capacity of priority {} software tasks: {} -> {}",
+                priority, old, new
+            ),
+
+            Change::Dispatcher { priority, old, new } => write!(
+                f,
+                "dispatcher of priority {}: {} -> {}",
+                priority,
+                dispatcher(old),
+                dispatcher(new)
+            ),
+
+            Change::SendType { ty, added: true } => write!(f, "`{}` must now implement `Send`", ty),
+            Change::SendType { ty, added: false } => {
+                write!(f, "`{}` no longer needs to implement `Send`", ty)
+            }
+
+            Change::SyncType { ty, added: true } => write!(f, "`{}` must now implement `Sync`", ty),
+            Change::SyncType { ty, added: false } => {
+                write!(f, "`{}` no longer needs to implement `Sync`", ty)
+            }
+        }
+    }
+}
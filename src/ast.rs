@@ -47,6 +47,47 @@ pub struct App {
     pub software_tasks: Map<SoftwareTask>,
 }
 
+/// The contents of an `App`, moved out of it; see `App::into_parts`
+#[derive(Debug)]
+#[non_exhaustive]
+pub struct AppParts {
+    /// The arguments to the `#[app]` attribute
+    pub args: AppArgs,
+
+    /// The name of the module on which the `#[app]` attribute has been placed
+    pub name: Ident,
+
+    /// Vector containing the `#[init]` function
+    pub inits: Inits,
+
+    /// Vector containing the `#[idle]` function
+    pub idles: Idles,
+
+    /// Monotonic clocks
+    pub monotonics: Map<Monotonic>,
+
+    /// Late (runtime initialized) resources
+    pub late_resources: Map<LateResource>,
+
+    /// Early (compile time initialized) resources
+    pub resources: Map<Resource>,
+
+    /// User imports
+    pub user_imports: Vec<ItemUse>,
+
+    /// User code
+    pub user_code: Vec<Item>,
+
+    /// User types
+    pub user_types: Vec<ItemType>,
+
+    /// Hardware tasks: `#[task(binds = ..)]`s
+    pub hardware_tasks: Map<HardwareTask>,
+
+    /// Software tasks: `#[task]`
+    pub software_tasks: Map<SoftwareTask>,
+}
+
 /// Interrupts used to dispatch software tasks
 pub type ExternInterrupts = Map<ExternInterrupt>;
 
@@ -69,6 +110,12 @@ pub struct AppArgs {
 
     /// Interrupts used to dispatch software tasks
     pub extern_interrupts: ExternInterrupts,
+
+    /// The arguments as they were written, in textual order, or sorted by name after
+    /// `App::canonicalize`
+    ///
+    /// Each identifier carries the span of the argument's name
+    pub arg_order: Vec<Ident>,
 }
 
 /// `init` function
@@ -106,11 +153,22 @@ pub struct Init {
 pub struct InitArgs {
     /// Late resources that will be initialized
     ///
-    /// NOTE do not use this field for codegen; use `Analysis.late_resources` instead
+    /// NOTE do not use this field for codegen; use `Analysis.late_resource_assignment` instead
     pub late: Set<Ident>,
 
     /// Resources that can be accessed from this context
     pub resources: Resources,
+
+    /// Whether `init` explicitly requested the core peripherals (`core_peripherals = true`)
+    ///
+    /// NOTE do not use this field for codegen; use `Analysis.core_peripherals_user` instead
+    pub core_peripherals: bool,
+
+    /// The arguments as they were written, in textual order, or sorted by name after
+    /// `App::canonicalize`
+    ///
+    /// Each identifier carries the span of the argument's name
+    pub arg_order: Vec<Ident>,
 }
 
 /// The `idle` context
@@ -142,6 +200,17 @@ pub struct Idle {
 pub struct IdleArgs {
     /// Resources that can be accessed from this context
     pub resources: Resources,
+
+    /// Whether `idle` requested the core peripherals (`core_peripherals = true`)
+    ///
+    /// NOTE do not use this field for codegen; use `Analysis.core_peripherals_user` instead
+    pub core_peripherals: bool,
+
+    /// The arguments as they were written, in textual order, or sorted by name after
+    /// `App::canonicalize`
+    ///
+    /// Each identifier carries the span of the argument's name
+    pub arg_order: Vec<Ident>,
 }
 
 /// Resource properties
@@ -177,7 +246,7 @@ pub struct LateResource {
     /// `#[cfg]` attributes like `#[cfg(debug_assertions)]`
     pub cfgs: Vec<Attribute>,
 
-    /// Attributes that will apply to this resource
+    /// Attributes that will apply to this resource, e.g. `#[link_section = ".ccmram"]`
     pub attrs: Vec<Attribute>,
 
     /// The type of this resource
@@ -187,6 +256,19 @@ pub struct LateResource {
     pub properties: ResourceProperties,
 }
 
+/// A late resource declaration
+#[derive(Clone, Copy, Debug)]
+pub struct LateResourceDecl<'a> {
+    /// The name of the resource
+    pub name: &'a Ident,
+
+    /// The type of the resource
+    pub ty: &'a Type,
+
+    /// `#[cfg]` attributes like `#[cfg(debug_assertions)]`
+    pub cfgs: &'a [Attribute],
+}
+
 /// Monotonic
 #[derive(Debug)]
 #[non_exhaustive]
@@ -216,6 +298,12 @@ pub struct MonotonicArgs {
 
     /// If this is the default monotonic
     pub default: bool,
+
+    /// The arguments as they were written, in textual order, or sorted by name after
+    /// `App::canonicalize`, which also adds the omitted arguments that have a default value
+    ///
+    /// Each identifier carries the span of the argument's name
+    pub arg_order: Vec<Ident>,
 }
 
 /// A software task
@@ -254,19 +342,45 @@ pub struct SoftwareTaskArgs {
     /// The task capacity: the maximum number of pending messages that can be queued
     pub capacity: u8,
 
+    /// The latency budget declared with `latency = ..`, in units of the user's choosing
+    ///
+    /// This is informational; see `Analysis.latency_hints`
+    pub latency: Option<u32>,
+
+    /// The resource that holds the memory pool this task's message payloads come from
+    ///
+    /// The pool is always an early resource and it's listed in `Analysis.locations` even if no
+    /// context accesses it
+    pub pool: Option<Ident>,
+
     /// The priority of this task
     pub priority: u8,
 
+    /// Whether the priority was written (`priority = ..`) rather than defaulted to 1
+    ///
+    /// `App::canonicalize` sets it as a defaulted priority is indistinguishable from a written one
+    pub priority_explicit: bool,
+
     /// Resources that can be accessed from this context
     pub resources: Resources,
+
+    /// The arguments as they were written, in textual order, or sorted by name after
+    /// `App::canonicalize`, which also adds the omitted arguments that have a default value
+    ///
+    /// Each identifier carries the span of the argument's name
+    pub arg_order: Vec<Ident>,
 }
 
 impl Default for SoftwareTaskArgs {
     fn default() -> Self {
         Self {
             capacity: 1,
+            latency: None,
+            pool: None,
             priority: 1,
+            priority_explicit: false,
             resources: Resources::new(),
+            arg_order: Vec::new(),
         }
     }
 }
@@ -304,11 +418,33 @@ pub struct HardwareTaskArgs {
     /// The interrupt or exception that this task is bound to
     pub binds: Ident,
 
+    /// The latency budget declared with `latency = ..`, in units of the user's choosing
+    ///
+    /// This is informational; see `Analysis.latency_hints`
+    pub latency: Option<u32>,
+
     /// The priority of this task
     pub priority: u8,
 
+    /// Whether the priority was written (`priority = ..`) rather than defaulted to 1
+    ///
+    /// `App::canonicalize` sets it as a defaulted priority is indistinguishable from a written one
+    pub priority_explicit: bool,
+
     /// Resources that can be accessed from this context
     pub resources: Resources,
+
+    /// The other interrupt sources that are wired to the same interrupt line as `binds`
+    ///
+    /// This is informational: it documents that the task handles all these sources and it's
+    /// listed in `Analysis.interrupts` for tooling. Each interrupt can still only be bound once
+    pub shares_line_with: Set<Ident>,
+
+    /// The arguments as they were written, in textual order, or sorted by name after
+    /// `App::canonicalize`, which also adds the omitted arguments that have a default value
+    ///
+    /// Each identifier carries the span of the argument's name
+    pub arg_order: Vec<Ident>,
 }
 
 /// A `static mut` variable local to and owned by a context
@@ -3,9 +3,68 @@ use std::collections::HashSet;
 use proc_macro2::Span;
 use syn::parse;
 
-use crate::ast::App;
+use crate::{analyze, ast::App, Settings};
+
+/// Names of items that the backend generates in each context's module
+const RESERVED_NAMES: &[&str] = &[
+    "Context",
+    "LateResources",
+    "Monotonics",
+    "Resources",
+    "Schedule",
+    "Spawn",
+];
+
+pub fn app(app: &App, settings: &Settings) -> parse::Result<()> {
+    // Check that no context is named after an item that the backend generates
+    let contexts = app
+        .inits
+        .iter()
+        .map(|init| &init.name)
+        .chain(app.idles.iter().map(|idle| &idle.name))
+        .chain(app.hardware_tasks.keys())
+        .chain(app.software_tasks.keys());
+    for name in contexts {
+        if RESERVED_NAMES.iter().any(|reserved| name == reserved) {
+            return Err(parse::Error::new(
+                name.span(),
+                format!(
+                    "`{}` is reserved for generated code; rename this function, e.g. to `{}`",
+                    name,
+                    snake_case(&name.to_string())
+                ),
+            ));
+        }
+    }
+
+    // Check that tasks and resources are named in `snake_case`
+    if settings.enforce_snake_case_tasks {
+        let tasks = app
+            .hardware_tasks
+            .keys()
+            .chain(app.software_tasks.keys())
+            .map(|name| (name, "task"));
+        let resources = app
+            .resources
+            .keys()
+            .chain(app.late_resources.keys())
+            .map(|name| (name, "resource"));
+        for (name, kind) in tasks.chain(resources) {
+            let name_str = name.to_string();
+            if !is_snake_case(&name_str) {
+                return Err(parse::Error::new(
+                    name.span(),
+                    format!(
+                        "{} names must be `snake_case`; rename this {} to `{}`",
+                        kind,
+                        kind,
+                        snake_case(&name_str)
+                    ),
+                ));
+            }
+        }
+    }
 
-pub fn app(app: &App) -> parse::Result<()> {
     // Check that all referenced resources have been declared
     // Check that resources are NOT `Exclusive`-ly shared
     let mut owners = HashSet::new();
@@ -65,6 +124,113 @@ pub fn app(app: &App) -> parse::Result<()> {
         }
     }
 
+    // Check that `idle` only has `Access::Shared` resources, if the backend requires it
+    if settings.idle_shared_access_only {
+        if let Some(idle) = app.idles.first() {
+            if let Some((name, _)) = idle
+                .args
+                .resources
+                .iter()
+                .find(|(_, access)| access.is_exclusive())
+            {
+                return Err(parse::Error::new(
+                    name.span(),
+                    "idle may only take shared references under this backend; \
+                     wrap mutation in a task",
+                ));
+            }
+        }
+    }
+
+    // Check that an explicit `late` list names exactly the late resources
+    if let Some(init) = app.inits.first() {
+        if !init.args.late.is_empty() {
+            if let Some(name) = init
+                .args
+                .late
+                .iter()
+                .find(|name| !app.late_resources.contains_key(*name))
+            {
+                return Err(parse::Error::new(
+                    name.span(),
+                    "this is not a late resource",
+                ));
+            }
+
+            if let Some(name) = app
+                .late_resources
+                .keys()
+                .find(|name| !init.args.late.contains(*name))
+            {
+                return Err(parse::Error::new(
+                    name.span(),
+                    format!(
+                        "late resource `{}` is not initialized by `init`; add it to the `late` list",
+                        name
+                    ),
+                ));
+            }
+        }
+    }
+
+    // Check that late resources are not read-only; a read-only resource needs an initial value
+    if settings.reject_read_only_late_resources {
+        let shared_accesses = app
+            .resource_accesses()
+            .filter_map(|(_, name, access)| if access.is_shared() { Some(name) } else { None })
+            .collect::<HashSet<_>>();
+        for name in app.late_resources.keys() {
+            if shared_accesses.contains(name) && !owners.contains(name) {
+                return Err(parse::Error::new(
+                    name.span(),
+                    "this resource is only accessed through shared references (`&`); give it an \
+                     initial value with `#[init(..)]`",
+                ));
+            }
+        }
+    }
+
+    // Check that at most one context requests the core peripherals
+    if settings.parse_core_peripherals {
+        if let (Some(init), Some(idle)) = (app.inits.first(), app.idles.first()) {
+            if init.args.core_peripherals && idle.args.core_peripherals {
+                let span = idle
+                    .args
+                    .arg_order
+                    .iter()
+                    .find(|arg| *arg == "core_peripherals")
+                    .map(|arg| arg.span())
+                    .unwrap_or_else(Span::call_site);
+
+                return Err(parse::Error::new(
+                    span,
+                    "the core peripherals can only be taken by one context; \
+                     `init` already requested them",
+                ));
+            }
+        }
+    }
+
+    // Check that memory pools are declared resources that exist before `init` runs
+    for task in app.software_tasks.values() {
+        if let Some(pool) = &task.args.pool {
+            if app.late_resources.contains_key(pool) {
+                return Err(parse::Error::new(
+                    pool.span(),
+                    "this pool is a late resource but `init` may spawn this task before \
+                     initializing it; give the pool an initial value with `#[init(..)]`",
+                ));
+            }
+
+            if !app.resources.contains_key(pool) {
+                return Err(parse::Error::new(
+                    pool.span(),
+                    "this resource has NOT been declared",
+                ));
+            }
+        }
+    }
+
     // Check that all late resources are covered by `init::LateResources`
     let late_resources_set = app.late_resources.keys().collect::<HashSet<_>>();
     if !late_resources_set.is_empty() {
@@ -77,17 +243,117 @@ pub fn app(app: &App) -> parse::Result<()> {
         }
     }
 
-    // check that external interrupts are not used as hardware tasks
-    for task in app.hardware_tasks.values() {
-        let binds = &task.args.binds;
+    // Check that the device peripherals are only requested when there's a device to take them from
+    // (there's no device on the host)
+    if !settings.host_test_mode && app.args.peripherals && app.args.device.is_none() {
+        if let Some(arg) = app.args.arg_order.iter().find(|arg| *arg == "peripherals") {
+            return Err(parse::Error::new(
+                arg.span(),
+                "`peripherals = true` requires the `device` argument",
+            ));
+        }
+    }
+
+    // Check that software tasks have enough dispatchers if the list of dispatchers was given (on
+    // the host, software tasks are not dispatched from interrupts)
+    let bounds = analyze::dispatcher_bounds(app);
+    let needed = if settings.dispatcher_check_lower_bound {
+        bounds.lower
+    } else {
+        bounds.upper
+    };
+    if !settings.host_test_mode && app.args.extern_interrupts.len() < needed {
+        if let Some(arg) = app.args.arg_order.iter().find(|arg| *arg == "dispatchers") {
+            let message = if app.args.extern_interrupts.is_empty() {
+                "the list of dispatchers is empty but software tasks need one dispatcher per \
+                 priority level"
+                    .to_string()
+            } else {
+                format!(
+                    "not enough dispatchers: software tasks use {} priority levels but only {} \
+                     dispatchers were given",
+                    needed,
+                    app.args.extern_interrupts.len()
+                )
+            };
+
+            return Err(parse::Error::new(arg.span(), message));
+        }
+    }
+
+    // Check that no task nor monotonic runs at a reserved priority
+    let prioritized =
+        app.hardware_tasks
+            .iter()
+            .map(|(name, task)| (name, task.args.priority, &task.args.arg_order))
+            .chain(
+                app.software_tasks
+                    .iter()
+                    .map(|(name, task)| (name, task.args.priority, &task.args.arg_order)),
+            )
+            .chain(app.monotonics.iter().map(|(name, monotonic)| {
+                (name, monotonic.args.priority, &monotonic.args.arg_order)
+            }));
+    for (name, priority, arg_order) in prioritized {
+        if settings.reserved_priorities.contains(&priority) {
+            // point at the `priority` argument or, if the priority is implicit, at the name
+            let span = arg_order
+                .iter()
+                .find(|arg| *arg == "priority")
+                .map(|arg| arg.span())
+                .unwrap_or_else(|| name.span());
 
-        if app.args.extern_interrupts.contains_key(binds) {
             return Err(parse::Error::new(
-                binds.span(),
-                "dispatcher interrupts can't be used as hardware tasks",
+                span,
+                format!(
+                    "priority {} is reserved and can't be used by the application",
+                    priority
+                ),
             ));
         }
     }
 
+    // Hardware specific checks; skipped when compiling for the host. Hardware tasks only exist
+    // when `binds` is parsed
+    if settings.parse_binds && !settings.host_test_mode {
+        // check that external interrupts are not used as hardware tasks
+        for task in app.hardware_tasks.values() {
+            let binds = &task.args.binds;
+
+            if app.args.extern_interrupts.contains_key(binds) {
+                return Err(parse::Error::new(
+                    binds.span(),
+                    "dispatcher interrupts can't be used as hardware tasks",
+                ));
+            }
+        }
+    }
+
     Ok(())
 }
+
+// Same rules as the `non_snake_case` lint: no uppercase letters and no `__` between words
+fn is_snake_case(name: &str) -> bool {
+    let name = name.trim_start_matches("r#").trim_matches('_');
+
+    !name.chars().any(char::is_uppercase) && !name.contains("__")
+}
+
+// `LateResources` -> `late_resources`
+fn snake_case(name: &str) -> String {
+    let mut snake = String::new();
+
+    for (i, c) in name.chars().enumerate() {
+        if c.is_uppercase() {
+            if i != 0 {
+                snake.push('_');
+            }
+
+            snake.extend(c.to_lowercase());
+        } else {
+            snake.push(c);
+        }
+    }
+
+    snake
+}
@@ -10,10 +10,11 @@ extern crate proc_macro;
 
 use core::ops;
 use proc_macro::TokenStream;
+use std::collections::{BTreeMap, BTreeSet};
 
 use indexmap::{IndexMap, IndexSet};
 use proc_macro2::TokenStream as TokenStream2;
-use syn::Ident;
+use syn::{Attribute, Ident};
 
 use crate::ast::App;
 
@@ -23,13 +24,16 @@ pub mod ast;
 mod check;
 mod optimize;
 mod parse;
+pub mod prelude;
 #[cfg(test)]
 mod tests;
 
 /// An ordered map keyed by identifier
 pub type Map<T> = IndexMap<Ident, T>;
 
-/// An order set
+/// An ordered set
+///
+/// Iteration follows insertion order, which doesn't change between runs
 pub type Set<T> = IndexSet<T>;
 
 /// Immutable pointer
@@ -42,6 +46,11 @@ impl<T> P<T> {
     pub fn new(x: T) -> P<T> {
         P { ptr: Box::new(x) }
     }
+
+    /// Moves the value out of the box
+    pub fn into_inner(self) -> T {
+        *self.ptr
+    }
 }
 
 impl<T> ops::Deref for P<T> {
@@ -106,17 +115,64 @@ impl<'a> Context<'a> {
 
     /// Whether this context has resources
     pub fn has_resources(&self, app: &App) -> bool {
+        !self.resources(app).is_empty()
+    }
+
+    /// The priority at which this context runs
+    pub fn effective_priority(&self, app: &App) -> EffectivePriority {
         match *self {
-            Context::HardwareTask(name) => !app.hardware_tasks[name].args.resources.is_empty(),
-            Context::Idle => !app.idles.first().unwrap().args.resources.is_empty(),
-            Context::Init => !app.inits.first().unwrap().args.resources.is_empty(),
-            Context::SoftwareTask(name) => !app.software_tasks[name].args.resources.is_empty(),
+            Context::HardwareTask(name) => {
+                EffectivePriority::Interrupt(app.hardware_tasks[name].args.priority)
+            }
+            Context::Idle => EffectivePriority::Thread,
+            Context::Init => EffectivePriority::PreRuntime,
+            Context::SoftwareTask(name) => {
+                EffectivePriority::Interrupt(app.software_tasks[name].args.priority)
+            }
+        }
+    }
+
+    // The resources this context accesses
+    pub(crate) fn resources(&self, app: &'a App) -> &'a ast::Resources {
+        match *self {
+            Context::HardwareTask(name) => &app.hardware_tasks[name].args.resources,
+            Context::Idle => &app.idles.first().unwrap().args.resources,
+            Context::Init => &app.inits.first().unwrap().args.resources,
+            Context::SoftwareTask(name) => &app.software_tasks[name].args.resources,
+        }
+    }
+}
+
+/// The priority at which a context runs
+///
+/// The variants are ordered from the lowest to the highest priority: `init` runs before
+/// anything can preempt it, `idle` runs in thread mode and everything else in an interrupt
+/// handler
+#[derive(Clone, Copy, Debug, Eq, Hash, Ord, PartialEq, PartialOrd)]
+pub enum EffectivePriority {
+    /// `init`, which runs before the scheduler starts and doesn't take part in the ceiling
+    /// computation
+    PreRuntime,
+
+    /// `idle`, which runs in thread mode at priority 0
+    Thread,
+
+    /// A hardware or software task, which runs in an interrupt handler at the given priority
+    Interrupt(u8),
+}
+
+impl EffectivePriority {
+    /// The numeric priority; `None` for `init`
+    pub fn priority(self) -> Option<u8> {
+        match self {
+            EffectivePriority::PreRuntime => None,
+            EffectivePriority::Thread => Some(0),
+            EffectivePriority::Interrupt(priority) => Some(priority),
         }
     }
 }
 
 /// Parser and optimizer configuration
-#[derive(Default)]
 #[non_exhaustive]
 pub struct Settings {
     /// Whether to accept the `binds` argument in `#[task]` or not
@@ -125,6 +181,152 @@ pub struct Settings {
     pub parse_extern_interrupt: bool,
     /// Whether to "compress" priorities or not
     pub optimize_priorities: bool,
+    /// Whether `idle` is restricted to shared (`&x`) resource accesses or not
+    pub idle_shared_access_only: bool,
+    /// Whether to parse `core_peripherals = true` on `init` and `idle` or not
+    pub parse_core_peripherals: bool,
+    /// Whether the target lacks the BASEPRI register (e.g. ARMv6-M) or not
+    pub no_basepri: bool,
+    /// Whether to skip the checks that only make sense on the target hardware or not
+    ///
+    /// Structural checks (names, resources, capacities) are still performed so that the
+    /// application can be compiled and unit tested on the host
+    pub host_test_mode: bool,
+    /// Named constants that may be used as task priorities, e.g. `priority = HIGH`
+    pub priority_consts: BTreeMap<String, u8>,
+    /// Whether a hardware task and a software task may share a name when their `#[cfg]`s can
+    /// never be enabled at the same time
+    pub allow_name_collisions_across_cfg: bool,
+    /// Evaluates a `#[cfg]` attribute, returning whether it's enabled
+    ///
+    /// Used to decide whether two `#[cfg]` lists are disjoint; without it only `x` vs `not(x)`
+    /// is recognized
+    pub evaluate_cfg: Option<fn(&Attribute) -> bool>,
+    /// The return type that `#[init]` must have
+    pub init_return_type: InitReturnKind,
+    /// Whether `#[idle]` may return `()` instead of `!` when its body ends in a `loop` that no
+    /// `break`, `return` or `?` leaves
+    pub lenient_idle_never: bool,
+    /// Whether to check the number of dispatchers against the software tasks that are always
+    /// compiled in, instead of against all of them
+    ///
+    /// By default a software task behind a `#[cfg]` is assumed to be enabled; see
+    /// `Analysis::dispatcher_bounds`
+    pub dispatcher_check_lower_bound: bool,
+    /// Priority levels that the application may not use, e.g. because they belong to interrupt
+    /// handlers that are not part of the application
+    ///
+    /// Tasks and monotonics are rejected at these levels and `optimize_priorities` skips them.
+    /// Dispatchers run at the priority of their software tasks so they avoid them as well
+    pub reserved_priorities: BTreeSet<u8>,
+    /// Whether to leave out of `send_types` and `sync_types` the types that are `Send` and `Sync`
+    /// regardless of the application, like primitives and function pointers
+    pub elide_trivial_trait_asserts: bool,
+    /// Whether to parse the informational `latency = ..` argument of tasks or not
+    pub parse_latency_hints: bool,
+    /// Whether to reject task and resource names that are not `snake_case`
+    pub enforce_snake_case_tasks: bool,
+    /// Whether the parser flags `parse_binds` and `parse_extern_interrupt` may be enabled from the
+    /// `#[app]` attribute, e.g. `#[app(parse_binds)]`
+    ///
+    /// Backends that set these flags themselves should turn this off so that users can't
+    /// change how their application is parsed
+    pub accept_parser_flags_in_attribute: bool,
+    /// Whether to reject late resources that are only accessed through shared references (`&x`)
+    ///
+    /// Such a resource never changes after `init` so it could be given an initial value with
+    /// `#[init(..)]` instead, e.g. by backends that place read-only resources in read-only memory
+    pub reject_read_only_late_resources: bool,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            parse_binds: false,
+            parse_extern_interrupt: false,
+            optimize_priorities: false,
+            idle_shared_access_only: false,
+            parse_core_peripherals: false,
+            no_basepri: false,
+            host_test_mode: false,
+            priority_consts: BTreeMap::new(),
+            allow_name_collisions_across_cfg: false,
+            evaluate_cfg: None,
+            init_return_type: InitReturnKind::default(),
+            lenient_idle_never: false,
+            dispatcher_check_lower_bound: false,
+            reserved_priorities: BTreeSet::new(),
+            elide_trivial_trait_asserts: false,
+            parse_latency_hints: false,
+            enforce_snake_case_tasks: false,
+            accept_parser_flags_in_attribute: true,
+            reject_read_only_late_resources: false,
+        }
+    }
+}
+
+impl Settings {
+    // Enables the flag written as `#flag` in the `#[app]` attribute
+    pub(crate) fn parse_flag(&mut self, flag: &Ident) -> syn::parse::Result<()> {
+        let accept = self.accept_parser_flags_in_attribute;
+
+        match self.flag_mut(&flag.to_string()) {
+            Some(_) if !accept => Err(syn::parse::Error::new(
+                flag.span(),
+                "this flag is controlled by the backend",
+            )),
+
+            Some(value) => {
+                *value = true;
+                Ok(())
+            }
+
+            None => Err(syn::parse::Error::new(
+                flag.span(),
+                format!("unknown app setting `{}`", flag),
+            )),
+        }
+    }
+
+    // The parser flag named `name`, if there's one that can appear in the `#[app]` attribute
+    //
+    // Only the flags that select what syntax is accepted are listed here; the other settings
+    // change how the application is checked or compiled and are left to the backend
+    pub(crate) fn flag_mut(&mut self, name: &str) -> Option<&mut bool> {
+        Some(match name {
+            "parse_binds" => &mut self.parse_binds,
+            "parse_extern_interrupt" => &mut self.parse_extern_interrupt,
+            _ => return None,
+        })
+    }
+}
+
+/// The shapes of `#[init]` return types that the parser accepts
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum InitReturnKind {
+    /// `init::LateResources`
+    LateResources,
+    /// `(init::Shared, init::Local)`
+    SharedAndLocal,
+    /// `(init::LateResources, init::Monotonics)`
+    LateResourcesAndMonotonics,
+}
+
+impl Default for InitReturnKind {
+    fn default() -> Self {
+        InitReturnKind::LateResourcesAndMonotonics
+    }
+}
+
+impl InitReturnKind {
+    // The last path segment of each element of the return type; more than one means a tuple
+    pub(crate) fn types(self) -> &'static [&'static str] {
+        match self {
+            InitReturnKind::LateResources => &["LateResources"],
+            InitReturnKind::SharedAndLocal => &["Shared", "Local"],
+            InitReturnKind::LateResourcesAndMonotonics => &["LateResources", "Monotonics"],
+        }
+    }
 }
 
 /// Parses the input of the `#[app]` attribute
@@ -137,22 +339,60 @@ pub fn parse(
 }
 
 /// `proc_macro2::TokenStream` version of `parse`
+///
+/// Unlike `parse` this doesn't need the `proc_macro` runtime so it can also be used outside a
+/// procedural macro, e.g. from a build script or a test, on tokens parsed from a string
 pub fn parse2(
     args: TokenStream2,
     input: TokenStream2,
-    settings: Settings,
+    mut settings: Settings,
 ) -> Result<(P<ast::App>, P<analyze::Analysis>), syn::parse::Error> {
-    let mut app = parse::app(args, input, &settings)?;
-    check::app(&app)?;
+    let mut app = parse::app(args, input, &mut settings)?;
+    check::app(&app, &settings)?;
     optimize::app(&mut app, &settings);
 
-    match analyze::app(&app) {
+    match analyze::app(&app, &settings) {
         Err(e) => Err(e),
         // If no errors, return the app and analysis results
         Ok(analysis) => Ok((P::new(app), P::new(analysis))),
     }
 }
 
+/// A problem found while parsing or analyzing the application; see `app_from_tokens`
+pub type Diagnostic = syn::parse::Error;
+
+/// Error-accumulating version of `parse2`, meant for tools such as formatters and analyzers
+///
+/// Problems that don't prevent building the application, like unknown arguments (which are
+/// ignored), are returned alongside the `App` and its `Analysis`. Any other problem aborts and is
+/// returned after the problems found before it, in source order
+///
+/// `parse2` reports the first of these problems as an error
+pub fn app_from_tokens(
+    args: TokenStream2,
+    input: TokenStream2,
+    mut settings: Settings,
+) -> Result<(P<ast::App>, P<analyze::Analysis>, Vec<Diagnostic>), Vec<Diagnostic>> {
+    let mut diagnostics = vec![];
+
+    let result =
+        parse::app_recovering(args, input, &mut settings, &mut diagnostics).and_then(|mut app| {
+            check::app(&app, &settings)?;
+            optimize::app(&mut app, &settings);
+
+            let analysis = analyze::app(&app, &settings)?;
+            Ok((app, analysis))
+        });
+
+    match result {
+        Ok((app, analysis)) => Ok((P::new(app), P::new(analysis), diagnostics)),
+        Err(e) => {
+            diagnostics.push(e);
+            Err(diagnostics)
+        }
+    }
+}
+
 enum Either<A, B> {
     Left(A),
     Right(B),
@@ -19,10 +19,11 @@ pub fn app(app: &mut App, settings: &Settings) {
             )
             .collect::<BTreeSet<_>>();
 
+        // reserved priorities are skipped
         let map = priorities
             .iter()
             .cloned()
-            .zip(1..)
+            .zip((1..).filter(|priority| !settings.reserved_priorities.contains(priority)))
             .collect::<HashMap<_, _>>();
 
         for task in app.hardware_tasks.values_mut() {
@@ -8,7 +8,7 @@ mod monotonic;
 mod software_task;
 mod util;
 
-use proc_macro2::TokenStream as TokenStream2;
+use proc_macro2::{Span, TokenStream as TokenStream2};
 use syn::{
     braced, parenthesized,
     parse::{self, Parse, ParseStream, Parser},
@@ -22,11 +22,29 @@ use crate::{
 };
 
 // Parse the app, both app arguments and body (input)
-pub fn app(args: TokenStream2, input: TokenStream2, settings: &Settings) -> parse::Result<App> {
-    let args = AppArgs::parse(args)?;
+pub fn app(args: TokenStream2, input: TokenStream2, settings: &mut Settings) -> parse::Result<App> {
+    let mut recovered = vec![];
+    let app = app_recovering(args, input, settings, &mut recovered);
+
+    // Report the first problem, like a parser that stops at it would
+    match recovered.into_iter().next() {
+        Some(error) => Err(error),
+        None => app,
+    }
+}
+
+// Like `app` but problems that don't prevent building the `App` are pushed into `recovered`
+// instead of aborting the parse
+pub fn app_recovering(
+    args: TokenStream2,
+    input: TokenStream2,
+    settings: &mut Settings,
+    recovered: &mut Vec<parse::Error>,
+) -> parse::Result<App> {
+    let args = AppArgs::parse(args, settings, recovered)?;
     let input: Input = syn::parse2(input)?;
 
-    App::parse(args, input, settings)
+    App::parse(args, input, settings, recovered)
 }
 
 pub(crate) struct Input {
@@ -64,7 +82,11 @@ impl Parse for Input {
     }
 }
 
-fn init_idle_args(tokens: TokenStream2) -> parse::Result<InitArgs> {
+fn init_idle_args(
+    tokens: TokenStream2,
+    settings: &Settings,
+    recovered: &mut Vec<parse::Error>,
+) -> parse::Result<InitArgs> {
     (|input: ParseStream<'_>| -> parse::Result<InitArgs> {
         if input.is_empty() {
             return Ok(InitArgs::default());
@@ -72,6 +94,8 @@ fn init_idle_args(tokens: TokenStream2) -> parse::Result<InitArgs> {
 
         let mut late = None;
         let mut resources = None;
+        let mut core_peripherals = None;
+        let mut arg_order = vec![];
 
         let content;
         parenthesized!(content in input);
@@ -84,6 +108,8 @@ fn init_idle_args(tokens: TokenStream2) -> parse::Result<InitArgs> {
             let ident: Ident = content.parse()?;
             let _: Token![=] = content.parse()?;
 
+            arg_order.push(ident.clone());
+
             let ident_s = ident.to_string();
             match &*ident_s {
                 "late" => {
@@ -94,8 +120,18 @@ fn init_idle_args(tokens: TokenStream2) -> parse::Result<InitArgs> {
                         ));
                     }
 
+                    // [..]
+                    let span = content.span();
                     let idents = util::parse_idents(&content)?;
 
+                    if idents.is_empty() {
+                        return Err(parse::Error::new(
+                            span,
+                            "an empty `late` list is ambiguous; remove the argument to have `init` \
+                             initialize all the late resources",
+                        ));
+                    }
+
                     late = Some(idents);
                 }
 
@@ -110,8 +146,28 @@ fn init_idle_args(tokens: TokenStream2) -> parse::Result<InitArgs> {
                     resources = Some(util::parse_resources(&content)?);
                 }
 
+                "core_peripherals" if settings.parse_core_peripherals => {
+                    if core_peripherals.is_some() {
+                        return Err(parse::Error::new(
+                            ident.span(),
+                            "argument appears more than once",
+                        ));
+                    }
+
+                    let lit: LitBool = content.parse()?;
+                    core_peripherals = Some(lit.value);
+                }
+
                 _ => {
-                    return Err(parse::Error::new(ident.span(), "unexpected argument"));
+                    let valid: &[&str] = if settings.parse_core_peripherals {
+                        &["late", "resources", "core_peripherals"]
+                    } else {
+                        &["late", "resources"]
+                    };
+
+                    recovered.push(util::unexpected_argument(&ident, valid));
+                    arg_order.pop();
+                    util::skip_argument_value(&content)?;
                 }
             }
 
@@ -127,6 +183,10 @@ fn init_idle_args(tokens: TokenStream2) -> parse::Result<InitArgs> {
             late: late.unwrap_or_default(),
 
             resources: resources.unwrap_or_default(),
+
+            core_peripherals: core_peripherals.unwrap_or(false),
+
+            arg_order,
         })
     })
     .parse2(tokens)
@@ -135,6 +195,7 @@ fn init_idle_args(tokens: TokenStream2) -> parse::Result<InitArgs> {
 fn task_args(
     tokens: TokenStream2,
     settings: &Settings,
+    recovered: &mut Vec<parse::Error>,
 ) -> parse::Result<Either<HardwareTaskArgs, SoftwareTaskArgs>> {
     (|input: ParseStream<'_>| -> parse::Result<Either<HardwareTaskArgs, SoftwareTaskArgs>> {
         if input.is_empty() {
@@ -143,8 +204,12 @@ fn task_args(
 
         let mut binds = None;
         let mut capacity = None;
+        let mut latency = None;
+        let mut pool = None;
         let mut priority = None;
         let mut resources = None;
+        let mut shares_line_with = None;
+        let mut arg_order = vec![];
 
         let content;
         parenthesized!(content in input);
@@ -157,6 +222,8 @@ fn task_args(
             let ident: Ident = content.parse()?;
             let _: Token![=] = content.parse()?;
 
+            arg_order.push(ident.clone());
+
             let ident_s = ident.to_string();
             match &*ident_s {
                 "binds" if settings.parse_binds => {
@@ -168,10 +235,7 @@ fn task_args(
                     }
 
                     if capacity.is_some() {
-                        return Err(parse::Error::new(
-                            ident.span(),
-                            "hardware tasks can't use the `capacity` argument",
-                        ));
+                        return Err(binds_with_capacity(&arg_order));
                     }
 
                     // #ident
@@ -189,10 +253,7 @@ fn task_args(
                     }
 
                     if binds.is_some() {
-                        return Err(parse::Error::new(
-                            ident.span(),
-                            "hardware tasks can't use the `capacity` argument",
-                        ));
+                        return Err(binds_with_capacity(&arg_order));
                     }
 
                     // #lit
@@ -216,8 +277,8 @@ fn task_args(
                     capacity = Some(value.unwrap());
                 }
 
-                "priority" => {
-                    if priority.is_some() {
+                "latency" if settings.parse_latency_hints => {
+                    if latency.is_some() {
                         return Err(parse::Error::new(
                             ident.span(),
                             "argument appears more than once",
@@ -234,15 +295,84 @@ fn task_args(
                         ));
                     }
 
-                    let value = lit.base10_parse::<u8>().ok();
+                    let value = lit.base10_parse::<u32>().ok();
                     if value.is_none() || value == Some(0) {
                         return Err(parse::Error::new(
                             lit.span(),
-                            "this literal must be in the range 1...255",
+                            "this literal must be in the range 1...4294967295",
                         ));
                     }
 
-                    priority = Some(value.unwrap());
+                    latency = value;
+                }
+
+                "pool" => {
+                    if pool.is_some() {
+                        return Err(parse::Error::new(
+                            ident.span(),
+                            "argument appears more than once",
+                        ));
+                    }
+
+                    // #ident
+                    let ident: Ident = content.parse()?;
+
+                    pool = Some(ident);
+                }
+
+                "priority" => {
+                    if priority.is_some() {
+                        return Err(parse::Error::new(
+                            ident.span(),
+                            "argument appears more than once",
+                        ));
+                    }
+
+                    let value = if !settings.priority_consts.is_empty() && content.peek(Ident) {
+                        // #ident
+                        let name: Ident = content.parse()?;
+
+                        let value = match settings.priority_consts.get(&name.to_string()) {
+                            Some(value) => *value,
+                            None => {
+                                return Err(parse::Error::new(
+                                    name.span(),
+                                    "unknown priority constant",
+                                ));
+                            }
+                        };
+
+                        if value == 0 {
+                            return Err(parse::Error::new(
+                                name.span(),
+                                "this constant must be in the range 1...255",
+                            ));
+                        }
+
+                        value
+                    } else {
+                        // #lit
+                        let lit: LitInt = content.parse()?;
+
+                        if !lit.suffix().is_empty() {
+                            return Err(parse::Error::new(
+                                lit.span(),
+                                "this literal must be unsuffixed",
+                            ));
+                        }
+
+                        let value = lit.base10_parse::<u8>().ok();
+                        if value.is_none() || value == Some(0) {
+                            return Err(parse::Error::new(
+                                lit.span(),
+                                "this literal must be in the range 1...255",
+                            ));
+                        }
+
+                        value.unwrap()
+                    };
+
+                    priority = Some(value);
                 }
 
                 "resources" => {
@@ -256,8 +386,31 @@ fn task_args(
                     resources = Some(util::parse_resources(&content)?);
                 }
 
+                "shares_line_with" if settings.parse_binds => {
+                    if shares_line_with.is_some() {
+                        return Err(parse::Error::new(
+                            ident.span(),
+                            "argument appears more than once",
+                        ));
+                    }
+
+                    // [#ident, ..]
+                    shares_line_with = Some(util::parse_idents(&content)?);
+                }
+
                 _ => {
-                    return Err(parse::Error::new(ident.span(), "unexpected argument"));
+                    let mut valid = vec!["capacity", "pool", "priority", "resources"];
+                    if settings.parse_binds {
+                        valid.extend(&["binds", "shares_line_with"]);
+                    }
+                    if settings.parse_latency_hints {
+                        valid.push("latency");
+                    }
+                    valid.sort_unstable();
+
+                    recovered.push(util::unexpected_argument(&ident, &valid));
+                    arg_order.pop();
+                    util::skip_argument_value(&content)?;
                 }
             }
 
@@ -268,31 +421,92 @@ fn task_args(
             // ,
             let _: Token![,] = content.parse()?;
         }
+        let priority_explicit = priority.is_some();
         let priority = priority.unwrap_or(1);
         let resources = resources.unwrap_or_default();
 
+        if binds.is_some() && pool.is_some() {
+            let span = arg_order
+                .iter()
+                .find(|arg| *arg == "pool")
+                .map(|arg| arg.span())
+                .unwrap_or_else(Span::call_site);
+
+            return Err(parse::Error::new(
+                span,
+                "`pool` only applies to software tasks, which receive messages",
+            ));
+        }
+
+        if binds.is_none() && shares_line_with.is_some() {
+            let span = arg_order
+                .iter()
+                .find(|arg| *arg == "shares_line_with")
+                .map(|arg| arg.span())
+                .unwrap_or_else(Span::call_site);
+
+            return Err(parse::Error::new(
+                span,
+                "`shares_line_with` only applies to hardware tasks; add the `binds` argument",
+            ));
+        }
+
         Ok(if let Some(binds) = binds {
             Either::Left(HardwareTaskArgs {
                 binds,
+                latency,
                 priority,
+                priority_explicit,
                 resources,
+                shares_line_with: shares_line_with.unwrap_or_default(),
+                arg_order,
             })
         } else {
             Either::Right(SoftwareTaskArgs {
                 capacity: capacity.unwrap_or(1),
+                latency,
+                pool,
                 priority,
+                priority_explicit,
                 resources,
+                arg_order,
             })
         })
     })
     .parse2(tokens)
 }
 
-fn monotonic_args(tokens: TokenStream2) -> parse::Result<MonotonicArgs> {
+// `binds` and `capacity` were both given; `arg_order` contains both argument names
+fn binds_with_capacity(arg_order: &[Ident]) -> parse::Error {
+    let span = |name: &str| {
+        arg_order
+            .iter()
+            .find(|arg| *arg == name)
+            .map(|arg| arg.span())
+            .unwrap_or_else(Span::call_site)
+    };
+
+    let mut err = parse::Error::new(
+        span("binds"),
+        "`binds` makes this a hardware task, which runs when its interrupt fires and can't be \
+         queued; remove `binds` to get a software task with a message queue",
+    );
+    err.combine(parse::Error::new(
+        span("capacity"),
+        "`capacity` only applies to software tasks",
+    ));
+    err
+}
+
+fn monotonic_args(
+    tokens: TokenStream2,
+    recovered: &mut Vec<parse::Error>,
+) -> parse::Result<MonotonicArgs> {
     (|input: ParseStream<'_>| -> parse::Result<MonotonicArgs> {
         let mut binds = None;
         let mut priority = None;
         let mut default = None;
+        let mut arg_order = vec![];
 
         let content;
         parenthesized!(content in input);
@@ -305,6 +519,8 @@ fn monotonic_args(tokens: TokenStream2) -> parse::Result<MonotonicArgs> {
             let ident: Ident = content.parse()?;
             let _: Token![=] = content.parse()?;
 
+            arg_order.push(ident.clone());
+
             let ident_s = ident.to_string();
             match &*ident_s {
                 "binds" => {
@@ -363,7 +579,12 @@ fn monotonic_args(tokens: TokenStream2) -> parse::Result<MonotonicArgs> {
                 }
 
                 _ => {
-                    return Err(parse::Error::new(ident.span(), "unexpected argument"));
+                    recovered.push(util::unexpected_argument(
+                        &ident,
+                        &["binds", "priority", "default"],
+                    ));
+                    arg_order.pop();
+                    util::skip_argument_value(&content)?;
                 }
             }
 
@@ -389,6 +610,7 @@ fn monotonic_args(tokens: TokenStream2) -> parse::Result<MonotonicArgs> {
             binds,
             priority,
             default,
+            arg_order,
         })
     })
     .parse2(tokens)
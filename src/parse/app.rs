@@ -5,8 +5,8 @@ use proc_macro2::TokenStream as TokenStream2;
 use syn::{
     parse::{self, ParseStream, Parser},
     spanned::Spanned,
-    Expr, ExprArray, ExprParen, Fields, ForeignItem, Ident, Item, LitBool, Path, Token, Type,
-    Visibility,
+    Attribute, Expr, ExprArray, ExprParen, Fields, ForeignItem, Ident, Item, LitBool, Path, Token,
+    Type, Visibility,
 };
 
 use super::Input;
@@ -20,20 +20,38 @@ use crate::{
 };
 
 impl AppArgs {
-    pub(crate) fn parse(tokens: TokenStream2) -> parse::Result<Self> {
+    pub(crate) fn parse(
+        tokens: TokenStream2,
+        settings: &mut Settings,
+        recovered: &mut Vec<parse::Error>,
+    ) -> parse::Result<Self> {
         (|input: ParseStream<'_>| -> parse::Result<Self> {
             let mut custom = Set::new();
             let mut device = None;
             let mut peripherals = true;
             let mut extern_interrupts = ExternInterrupts::new();
+            let mut arg_order = vec![];
 
             loop {
                 if input.is_empty() {
                     break;
                 }
 
-                // #ident = ..
                 let ident: Ident = input.parse()?;
+
+                // #flag
+                if input.is_empty() || input.peek(Token![,]) {
+                    settings.parse_flag(&ident)?;
+
+                    if input.is_empty() {
+                        break;
+                    }
+
+                    let _: Token![,] = input.parse()?;
+                    continue;
+                }
+
+                // #ident = ..
                 let _eq_token: Token![=] = input.parse()?;
 
                 if custom.contains(&ident) {
@@ -44,6 +62,7 @@ impl AppArgs {
                 }
 
                 custom.insert(ident.clone());
+                arg_order.push(ident.clone());
 
                 let ks = ident.to_string();
 
@@ -114,7 +133,12 @@ impl AppArgs {
                         }
                     }
                     _ => {
-                        return Err(parse::Error::new(ident.span(), "unexpected argument"));
+                        recovered.push(util::unexpected_argument(
+                            &ident,
+                            &["device", "peripherals", "dispatchers"],
+                        ));
+                        arg_order.pop();
+                        util::skip_argument_value(input)?;
                     }
                 }
 
@@ -130,6 +154,7 @@ impl AppArgs {
                 device,
                 peripherals,
                 extern_interrupts,
+                arg_order,
             })
         })
         .parse2(tokens)
@@ -137,7 +162,12 @@ impl AppArgs {
 }
 
 impl App {
-    pub(crate) fn parse(args: AppArgs, input: Input, settings: &Settings) -> parse::Result<Self> {
+    pub(crate) fn parse(
+        args: AppArgs,
+        input: Input,
+        settings: &Settings,
+        recovered: &mut Vec<parse::Error>,
+    ) -> parse::Result<Self> {
         let mut inits = Vec::new();
         let mut idles = Vec::new();
 
@@ -152,19 +182,34 @@ impl App {
         let mut user_types = vec![];
 
         let mut seen_idents = HashSet::<Ident>::new();
-        let mut bindings = HashSet::<Ident>::new();
+        // The `#[cfg]`s of each item that binds an interrupt
+        let mut bindings = Map::<Vec<Vec<Attribute>>>::new();
         let mut monotonic_types = HashSet::<Type>::new();
 
-        let mut check_binding = |ident: &Ident| {
-            if bindings.contains(ident) {
+        // Items whose `#[cfg]`s are disjoint may bind the same interrupt as they'll never be
+        // compiled in together
+        let mut check_binding = |ident: &Ident, attrs: &[Attribute]| {
+            let cfgs = attrs
+                .iter()
+                .filter(|attr| util::attr_eq(attr, "cfg"))
+                .collect::<Vec<_>>();
+            let bound = bindings.entry(ident.clone()).or_default();
+
+            if bound.iter().any(|other| {
+                !util::cfgs_disjoint(
+                    &cfgs,
+                    &other.iter().collect::<Vec<_>>(),
+                    settings.evaluate_cfg,
+                )
+            }) {
                 return Err(parse::Error::new(
                     ident.span(),
                     "this interrupt is already bound",
                 ));
-            } else {
-                bindings.insert(ident.clone());
             }
 
+            bound.push(cfgs.into_iter().cloned().collect());
+
             Ok(())
         };
 
@@ -198,12 +243,8 @@ impl App {
             match item {
                 Item::Fn(mut item) => {
                     let span = item.sig.ident.span();
-                    if let Some(pos) = item
-                        .attrs
-                        .iter()
-                        .position(|attr| util::attr_eq(attr, "init"))
-                    {
-                        let args = InitArgs::parse(item.attrs.remove(pos).tokens)?;
+                    if let Some(tokens) = util::extract_args(&mut item.attrs, "init")? {
+                        let args = InitArgs::parse(tokens, settings, recovered)?;
 
                         // If an init function already exists, error
                         if !inits.is_empty() {
@@ -215,13 +256,9 @@ impl App {
 
                         check_ident(&item.sig.ident)?;
 
-                        inits.push(Init::parse(args, item)?);
-                    } else if let Some(pos) = item
-                        .attrs
-                        .iter()
-                        .position(|attr| util::attr_eq(attr, "idle"))
-                    {
-                        let args = IdleArgs::parse(item.attrs.remove(pos).tokens)?;
+                        inits.push(Init::parse(args, item, &input.ident, settings)?);
+                    } else if let Some(tokens) = util::extract_args(&mut item.attrs, "idle")? {
+                        let args = IdleArgs::parse(tokens, settings, recovered)?;
 
                         // If an idle function already exists, error
                         if !idles.is_empty() {
@@ -233,38 +270,67 @@ impl App {
 
                         check_ident(&item.sig.ident)?;
 
-                        idles.push(Idle::parse(args, item)?);
-                    } else if let Some(pos) = item
-                        .attrs
-                        .iter()
-                        .position(|attr| util::attr_eq(attr, "task"))
-                    {
-                        if hardware_tasks.contains_key(&item.sig.ident)
-                            || software_tasks.contains_key(&item.sig.ident)
-                        {
-                            return Err(parse::Error::new(
-                                span,
-                                "this task is defined multiple times",
-                            ));
-                        }
-
-                        match crate::parse::task_args(item.attrs.remove(pos).tokens, settings)? {
+                        idles.push(Idle::parse(args, item, &input.ident, settings)?);
+                    } else if let Some(tokens) = util::extract_args(&mut item.attrs, "task")? {
+                        match crate::parse::task_args(tokens, settings, recovered)? {
                             Either::Left(args) => {
-                                check_binding(&args.binds)?;
-                                check_ident(&item.sig.ident)?;
+                                // A software task with the same name may be a `#[cfg]` alternative
+                                let alternative = util::is_cfg_alternative(
+                                    &item.attrs,
+                                    software_tasks
+                                        .get(&item.sig.ident)
+                                        .map(|task| &task.cfgs[..]),
+                                    settings,
+                                );
+
+                                if hardware_tasks.contains_key(&item.sig.ident)
+                                    || (software_tasks.contains_key(&item.sig.ident)
+                                        && !alternative)
+                                {
+                                    return Err(parse::Error::new(
+                                        span,
+                                        "this task is defined multiple times",
+                                    ));
+                                }
+
+                                check_binding(&args.binds, &item.attrs)?;
+                                if !alternative {
+                                    check_ident(&item.sig.ident)?;
+                                }
 
                                 hardware_tasks.insert(
                                     item.sig.ident.clone(),
-                                    HardwareTask::parse(args, item)?,
+                                    HardwareTask::parse(args, item, &input.ident, settings)?,
                                 );
                             }
 
                             Either::Right(args) => {
-                                check_ident(&item.sig.ident)?;
+                                // A hardware task with the same name may be a `#[cfg]` alternative
+                                let alternative = util::is_cfg_alternative(
+                                    &item.attrs,
+                                    hardware_tasks
+                                        .get(&item.sig.ident)
+                                        .map(|task| &task.cfgs[..]),
+                                    settings,
+                                );
+
+                                if software_tasks.contains_key(&item.sig.ident)
+                                    || (hardware_tasks.contains_key(&item.sig.ident)
+                                        && !alternative)
+                                {
+                                    return Err(parse::Error::new(
+                                        span,
+                                        "this task is defined multiple times",
+                                    ));
+                                }
+
+                                if !alternative {
+                                    check_ident(&item.sig.ident)?;
+                                }
 
                                 software_tasks.insert(
                                     item.sig.ident.clone(),
-                                    SoftwareTask::parse(args, item)?,
+                                    SoftwareTask::parse(args, item, &input.ident, settings)?,
                                 );
                             }
                         }
@@ -360,11 +426,7 @@ impl App {
                     for item in mod_.items {
                         if let ForeignItem::Fn(mut item) = item {
                             let span = item.sig.ident.span();
-                            if let Some(pos) = item
-                                .attrs
-                                .iter()
-                                .position(|attr| util::attr_eq(attr, "task"))
-                            {
+                            if let Some(tokens) = util::extract_args(&mut item.attrs, "task")? {
                                 if hardware_tasks.contains_key(&item.sig.ident)
                                     || software_tasks.contains_key(&item.sig.ident)
                                 {
@@ -374,24 +436,26 @@ impl App {
                                     ));
                                 }
 
-                                if item.attrs.len() != 1 {
+                                if !item.attrs.is_empty() {
                                     return Err(parse::Error::new(
                                         span,
                                         "`extern` task required `#[task(..)]` attribute",
                                     ));
                                 }
 
-                                match crate::parse::task_args(
-                                    item.attrs.remove(pos).tokens,
-                                    settings,
-                                )? {
+                                match crate::parse::task_args(tokens, settings, recovered)? {
                                     Either::Left(args) => {
-                                        check_binding(&args.binds)?;
+                                        check_binding(&args.binds, &item.attrs)?;
                                         check_ident(&item.sig.ident)?;
 
                                         hardware_tasks.insert(
                                             item.sig.ident.clone(),
-                                            HardwareTask::parse_foreign(args, item)?,
+                                            HardwareTask::parse_foreign(
+                                                args,
+                                                item,
+                                                &input.ident,
+                                                settings,
+                                            )?,
                                         );
                                     }
 
@@ -400,7 +464,12 @@ impl App {
 
                                         software_tasks.insert(
                                             item.sig.ident.clone(),
-                                            SoftwareTask::parse_foreign(args, item)?,
+                                            SoftwareTask::parse_foreign(
+                                                args,
+                                                item,
+                                                &input.ident,
+                                                settings,
+                                            )?,
                                         );
                                     }
                                 }
@@ -447,9 +516,10 @@ impl App {
 
                         check_monotonic(&*type_item.ty)?;
 
-                        let args = MonotonicArgs::parse(type_item.attrs.remove(pos).tokens)?;
+                        let args =
+                            MonotonicArgs::parse(type_item.attrs.remove(pos).tokens, recovered)?;
 
-                        check_binding(&args.binds)?;
+                        check_binding(&args.binds, &type_item.attrs)?;
 
                         let monotonic = Monotonic::parse(args, type_item, span)?;
 
@@ -490,14 +560,14 @@ impl App {
 
 #[cfg(test)]
 mod tests {
-    use crate::ast::AppArgs;
+    use crate::{ast::AppArgs, Settings};
 
     #[test]
     fn parse_app_args_true() {
         let s = "peripherals = true";
 
         let stream: proc_macro2::TokenStream = s.parse().unwrap();
-        let result = AppArgs::parse(stream).unwrap();
+        let result = AppArgs::parse(stream, &mut Settings::default(), &mut vec![]).unwrap();
 
         assert!(result.peripherals);
     }
@@ -507,7 +577,7 @@ mod tests {
         let s = "peripherals = false";
 
         let stream: proc_macro2::TokenStream = s.parse().unwrap();
-        let result = AppArgs::parse(stream).unwrap();
+        let result = AppArgs::parse(stream, &mut Settings::default(), &mut vec![]).unwrap();
 
         assert!(!result.peripherals);
     }
@@ -517,8 +587,128 @@ mod tests {
         let s = "";
 
         let stream: proc_macro2::TokenStream = s.parse().unwrap();
-        let result = AppArgs::parse(stream).unwrap();
+        let result = AppArgs::parse(stream, &mut Settings::default(), &mut vec![]).unwrap();
 
         assert!(result.peripherals);
     }
+
+    #[test]
+    fn parse_app_args_dispatchers() {
+        // dispatchers are declared in the attribute; `extern` blocks hold externally defined tasks
+        let s = "dispatchers = [UART0, #[link_section = \".data.UART1\"] UART1]";
+
+        let stream: proc_macro2::TokenStream = s.parse().unwrap();
+        let result = AppArgs::parse(stream, &mut Settings::default(), &mut vec![]).unwrap();
+
+        let names = result
+            .extern_interrupts
+            .keys()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(names, ["UART0", "UART1"]);
+        assert!(result.extern_interrupts[0].attrs.is_empty());
+        assert_eq!(result.extern_interrupts[1].attrs.len(), 1);
+    }
+
+    #[test]
+    fn parse_app_args_flags() {
+        let s = "parse_binds, device = lm3s6965";
+
+        let stream: proc_macro2::TokenStream = s.parse().unwrap();
+        let mut settings = Settings::default();
+        let result = AppArgs::parse(stream, &mut settings, &mut vec![]).unwrap();
+
+        assert!(settings.parse_binds);
+        assert!(!settings.parse_extern_interrupt);
+        assert!(result.device.is_some());
+        assert_eq!(result.arg_order.len(), 1);
+    }
+
+    #[test]
+    fn parse_app_args_device() {
+        let s = "device = stm32f4::stm32f407";
+
+        let stream: proc_macro2::TokenStream = s.parse().unwrap();
+        let result = AppArgs::parse(stream, &mut Settings::default(), &mut vec![]).unwrap();
+
+        let device = result.device.unwrap();
+        let segments = device
+            .segments
+            .iter()
+            .map(|segment| segment.ident.to_string())
+            .collect::<Vec<_>>();
+        assert_eq!(segments, ["stm32f4", "stm32f407"]);
+    }
+
+    #[test]
+    fn parse_app_args_device_twice() {
+        let s = "device = stm32f4::stm32f407, device = lm3s6965";
+
+        let stream: proc_macro2::TokenStream = s.parse().unwrap();
+        let err = AppArgs::parse(stream, &mut Settings::default(), &mut vec![]).unwrap_err();
+
+        assert_eq!(err.to_string(), "argument appears more than once");
+        let start = err.span().start();
+        assert_eq!((start.line, start.column), (1, 29));
+    }
+
+    #[test]
+    fn parse_app_args_all_flags() {
+        // flags and arguments in any order
+        let s = "parse_extern_interrupt, dispatchers = [A], parse_binds, device = lm3s6965";
+
+        let stream: proc_macro2::TokenStream = s.parse().unwrap();
+        let mut settings = Settings::default();
+        let result = AppArgs::parse(stream, &mut settings, &mut vec![]).unwrap();
+
+        assert!(settings.parse_binds);
+        assert!(settings.parse_extern_interrupt);
+        assert_eq!(result.arg_order.len(), 2);
+    }
+
+    #[test]
+    fn parse_app_args_backend_setting() {
+        // settings that don't select the syntax can't be changed by the application
+        for setting in &["host_test_mode", "no_basepri", "optimize_priorities"] {
+            let stream: proc_macro2::TokenStream = setting.parse().unwrap();
+            let mut settings = Settings::default();
+            let err = AppArgs::parse(stream, &mut settings, &mut vec![]).unwrap_err();
+
+            assert_eq!(
+                err.to_string(),
+                format!("unknown app setting `{}`", setting)
+            );
+            assert!(!settings.host_test_mode);
+            assert!(!settings.no_basepri);
+            assert!(!settings.optimize_priorities);
+        }
+    }
+
+    #[test]
+    fn parse_app_args_unknown_flag() {
+        let s = "parse_binds, parse_schedule";
+
+        let stream: proc_macro2::TokenStream = s.parse().unwrap();
+        let err = AppArgs::parse(stream, &mut Settings::default(), &mut vec![]).unwrap_err();
+
+        assert_eq!(err.to_string(), "unknown app setting `parse_schedule`");
+        let start = err.span().start();
+        assert_eq!((start.line, start.column), (1, 13));
+    }
+
+    #[test]
+    fn parse_app_args_flags_rejected() {
+        let s = "device = lm3s6965, parse_binds";
+
+        let stream: proc_macro2::TokenStream = s.parse().unwrap();
+        let mut settings = Settings::default();
+        settings.accept_parser_flags_in_attribute = false;
+        let result = AppArgs::parse(stream, &mut settings, &mut vec![]);
+
+        assert_eq!(
+            result.unwrap_err().to_string(),
+            "this flag is controlled by the backend"
+        );
+        assert!(!settings.parse_binds);
+    }
 }
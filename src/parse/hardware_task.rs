@@ -1,18 +1,19 @@
-use syn::{parse, ForeignItemFn, ItemFn, Stmt};
+use syn::{parse, ForeignItemFn, Ident, ItemFn, Stmt};
 
 use crate::{
     ast::{HardwareTask, HardwareTaskArgs, Local},
-    parse::util,
-    Map,
+    parse::util::{self, ItemKind},
+    Map, Settings,
 };
 
 impl HardwareTask {
-    pub(crate) fn parse(args: HardwareTaskArgs, item: ItemFn) -> parse::Result<Self> {
+    pub(crate) fn parse(
+        args: HardwareTaskArgs,
+        item: ItemFn,
+        app: &Ident,
+        settings: &Settings,
+    ) -> parse::Result<Self> {
         let span = item.sig.ident.span();
-        let valid_signature = util::check_fn_signature(&item)
-            && item.sig.inputs.len() == 1
-            && util::type_is_unit(&item.sig.output);
-
         let name = item.sig.ident.to_string();
 
         if name == "init" || name == "idle" {
@@ -22,32 +23,21 @@ impl HardwareTask {
             ));
         }
 
-        if valid_signature {
-            if let Some((context, Ok(rest))) = util::parse_inputs(item.sig.inputs, &name) {
-                if rest.is_empty() {
-                    let (locals, stmts) = util::extract_locals(item.block.stmts)?;
-                    let (cfgs, attrs) = util::extract_cfgs(item.attrs);
+        let signature =
+            util::validate_signature(&item.vis, item.sig, ItemKind::HardwareTask, app, settings)?;
 
-                    return Ok(HardwareTask {
-                        args,
-                        cfgs,
-                        attrs,
-                        context,
-                        locals: Local::parse(locals)?,
-                        stmts,
-                        is_extern: false,
-                    });
-                }
-            }
-        }
+        let (locals, stmts) = util::extract_locals(item.block.stmts)?;
+        let (cfgs, attrs) = util::extract_cfgs(item.attrs);
 
-        Err(parse::Error::new(
-            span,
-            &format!(
-                "this task handler must have type signature `fn({}::Context)`",
-                name
-            ),
-        ))
+        Ok(HardwareTask {
+            args,
+            cfgs,
+            attrs,
+            context: signature.context,
+            locals: Local::parse(locals)?,
+            stmts,
+            is_extern: false,
+        })
     }
 }
 
@@ -55,12 +45,10 @@ impl HardwareTask {
     pub(crate) fn parse_foreign(
         args: HardwareTaskArgs,
         item: ForeignItemFn,
+        app: &Ident,
+        settings: &Settings,
     ) -> parse::Result<Self> {
         let span = item.sig.ident.span();
-        let valid_signature = util::check_foreign_fn_signature(&item)
-            && item.sig.inputs.len() == 1
-            && util::type_is_unit(&item.sig.output);
-
         let name = item.sig.ident.to_string();
 
         if name == "init" || name == "idle" {
@@ -70,30 +58,19 @@ impl HardwareTask {
             ));
         }
 
-        if valid_signature {
-            if let Some((context, Ok(rest))) = util::parse_inputs(item.sig.inputs, &name) {
-                if rest.is_empty() {
-                    let (cfgs, attrs) = util::extract_cfgs(item.attrs);
+        let signature =
+            util::validate_signature(&item.vis, item.sig, ItemKind::HardwareTask, app, settings)?;
 
-                    return Ok(HardwareTask {
-                        args,
-                        cfgs,
-                        attrs,
-                        context,
-                        locals: Map::<Local>::new(),
-                        stmts: Vec::<Stmt>::new(),
-                        is_extern: true,
-                    });
-                }
-            }
-        }
+        let (cfgs, attrs) = util::extract_cfgs(item.attrs);
 
-        Err(parse::Error::new(
-            span,
-            &format!(
-                "this task handler must have type signature `fn({}::Context)`",
-                name
-            ),
-        ))
+        Ok(HardwareTask {
+            args,
+            cfgs,
+            attrs,
+            context: signature.context,
+            locals: Map::<Local>::new(),
+            stmts: Vec::<Stmt>::new(),
+            is_extern: true,
+        })
     }
 }
@@ -1,50 +1,53 @@
 use proc_macro2::TokenStream as TokenStream2;
-use syn::{parse, ItemFn};
+use syn::{parse, parse_quote, Ident, ItemFn};
 
 use crate::{
     ast::{Idle, IdleArgs, Local},
-    parse::util,
+    parse::util::{self, ItemKind},
+    Settings,
 };
 
 impl IdleArgs {
-    pub(crate) fn parse(tokens: TokenStream2) -> parse::Result<Self> {
-        crate::parse::init_idle_args(tokens).map(|args| IdleArgs {
+    pub(crate) fn parse(
+        tokens: TokenStream2,
+        settings: &Settings,
+        recovered: &mut Vec<parse::Error>,
+    ) -> parse::Result<Self> {
+        crate::parse::init_idle_args(tokens, settings, recovered).map(|args| IdleArgs {
             resources: args.resources,
+            core_peripherals: args.core_peripherals,
+            arg_order: args.arg_order,
         })
     }
 }
 
 impl Idle {
-    pub(crate) fn parse(args: IdleArgs, item: ItemFn) -> parse::Result<Self> {
-        let valid_signature = util::check_fn_signature(&item)
-            && item.sig.inputs.len() == 1
-            && util::type_is_bottom(&item.sig.output);
-
-        let name = item.sig.ident.to_string();
-
-        if valid_signature {
-            if let Some((context, Ok(rest))) = util::parse_inputs(item.sig.inputs, &name) {
-                if rest.is_empty() {
-                    let (locals, stmts) = util::extract_locals(item.block.stmts)?;
-
-                    return Ok(Idle {
-                        args,
-                        attrs: item.attrs,
-                        context,
-                        locals: Local::parse(locals)?,
-                        name: item.sig.ident,
-                        stmts,
-                    });
-                }
-            }
+    pub(crate) fn parse(
+        args: IdleArgs,
+        item: ItemFn,
+        app: &Ident,
+        settings: &Settings,
+    ) -> parse::Result<Self> {
+        let name = item.sig.ident.clone();
+        let mut sig = item.sig;
+        if settings.lenient_idle_never
+            && util::type_is_unit(&sig.output)
+            && util::stmts_diverge(&item.block.stmts)
+        {
+            // the body never returns so `-> ()` is as good as `-> !`
+            sig.output = parse_quote!(-> !);
         }
+        let signature = util::validate_signature(&item.vis, sig, ItemKind::Idle, app, settings)?;
 
-        Err(parse::Error::new(
-            item.sig.ident.span(),
-            &format!(
-                "this `#[idle]` function must have signature `fn({}::Context) -> !`",
-                name
-            ),
-        ))
+        let (locals, stmts) = util::extract_locals(item.block.stmts)?;
+
+        Ok(Idle {
+            args,
+            attrs: item.attrs,
+            context: signature.context,
+            locals: Local::parse(locals)?,
+            name,
+            stmts,
+        })
     }
 }
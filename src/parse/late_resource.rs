@@ -1,5 +1,5 @@
 use proc_macro2::Span;
-use syn::{parse, Field, Visibility};
+use syn::{parse, spanned::Spanned, Field, Type, Visibility};
 
 use crate::{
     ast::{LateResource, ResourceProperties},
@@ -15,6 +15,33 @@ impl LateResource {
             ));
         }
 
+        // resources are `static` variables so their types must be concrete and sized
+        let mut ty = &item.ty;
+        while let Type::Paren(paren) = ty {
+            ty = &*paren.elem;
+        }
+        match ty {
+            Type::ImplTrait(ty) => {
+                return Err(parse::Error::new(
+                    ty.impl_token.span,
+                    "`impl Trait` can't be the type of a resource; resources must have a \
+                     concrete, sized type",
+                ));
+            }
+
+            Type::TraitObject(ty) => {
+                return Err(parse::Error::new(
+                    ty.dyn_token
+                        .map(|token| token.span)
+                        .unwrap_or_else(|| ty.span()),
+                    "trait objects are unsized so they can't be the type of a resource; put \
+                     the trait object behind a pointer, e.g. `Box<dyn Trait>`",
+                ));
+            }
+
+            _ => {}
+        }
+
         let (cfgs, mut attrs) = util::extract_cfgs(item.attrs.clone());
 
         let task_local = util::extract_task_local(&mut attrs)?;
@@ -8,8 +8,11 @@ use crate::{
 };
 
 impl MonotonicArgs {
-    pub(crate) fn parse(tokens: TokenStream2) -> parse::Result<Self> {
-        crate::parse::monotonic_args(tokens)
+    pub(crate) fn parse(
+        tokens: TokenStream2,
+        recovered: &mut Vec<parse::Error>,
+    ) -> parse::Result<Self> {
+        crate::parse::monotonic_args(tokens, recovered)
     }
 }
 
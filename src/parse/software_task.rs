@@ -1,45 +1,34 @@
-use syn::{parse, ForeignItemFn, ItemFn, Stmt};
+use syn::{parse, ForeignItemFn, Ident, ItemFn, Stmt};
 
 use crate::{
     ast::{Local, SoftwareTask, SoftwareTaskArgs},
-    parse::util,
-    Map,
+    parse::util::{self, ItemKind},
+    Map, Settings,
 };
 
 impl SoftwareTask {
-    pub(crate) fn parse(args: SoftwareTaskArgs, item: ItemFn) -> parse::Result<Self> {
-        let valid_signature =
-            util::check_fn_signature(&item) && util::type_is_unit(&item.sig.output);
-
-        let span = item.sig.ident.span();
-
-        let name = item.sig.ident.to_string();
-
-        if valid_signature {
-            if let Some((context, Ok(inputs))) = util::parse_inputs(item.sig.inputs, &name) {
-                let (locals, stmts) = util::extract_locals(item.block.stmts)?;
-                let (cfgs, attrs) = util::extract_cfgs(item.attrs);
-
-                return Ok(SoftwareTask {
-                    args,
-                    attrs,
-                    cfgs,
-                    context,
-                    inputs,
-                    locals: Local::parse(locals)?,
-                    stmts,
-                    is_extern: false,
-                });
-            }
-        }
-
-        Err(parse::Error::new(
-            span,
-            &format!(
-                "this task handler must have type signature `fn({}::Context, ..)`",
-                name
-            ),
-        ))
+    pub(crate) fn parse(
+        args: SoftwareTaskArgs,
+        item: ItemFn,
+        app: &Ident,
+        settings: &Settings,
+    ) -> parse::Result<Self> {
+        let signature =
+            util::validate_signature(&item.vis, item.sig, ItemKind::SoftwareTask, app, settings)?;
+
+        let (locals, stmts) = util::extract_locals(item.block.stmts)?;
+        let (cfgs, attrs) = util::extract_cfgs(item.attrs);
+
+        Ok(SoftwareTask {
+            args,
+            attrs,
+            cfgs,
+            context: signature.context,
+            inputs: signature.inputs,
+            locals: Local::parse(locals)?,
+            stmts,
+            is_extern: false,
+        })
     }
 }
 
@@ -47,37 +36,23 @@ impl SoftwareTask {
     pub(crate) fn parse_foreign(
         args: SoftwareTaskArgs,
         item: ForeignItemFn,
+        app: &Ident,
+        settings: &Settings,
     ) -> parse::Result<Self> {
-        let valid_signature =
-            util::check_foreign_fn_signature(&item) && util::type_is_unit(&item.sig.output);
-
-        let span = item.sig.ident.span();
-
-        let name = item.sig.ident.to_string();
-
-        if valid_signature {
-            if let Some((context, Ok(inputs))) = util::parse_inputs(item.sig.inputs, &name) {
-                let (cfgs, attrs) = util::extract_cfgs(item.attrs);
-
-                return Ok(SoftwareTask {
-                    args,
-                    attrs,
-                    cfgs,
-                    context,
-                    inputs,
-                    locals: Map::<Local>::new(),
-                    stmts: Vec::<Stmt>::new(),
-                    is_extern: true,
-                });
-            }
-        }
-
-        Err(parse::Error::new(
-            span,
-            &format!(
-                "this task handler must have type signature `fn({}::Context, ..)`",
-                name
-            ),
-        ))
+        let signature =
+            util::validate_signature(&item.vis, item.sig, ItemKind::SoftwareTask, app, settings)?;
+
+        let (cfgs, attrs) = util::extract_cfgs(item.attrs);
+
+        Ok(SoftwareTask {
+            args,
+            attrs,
+            cfgs,
+            context: signature.context,
+            inputs: signature.inputs,
+            locals: Map::<Local>::new(),
+            stmts: Vec::<Stmt>::new(),
+            is_extern: true,
+        })
     }
 }
@@ -1,15 +1,18 @@
+use core::cmp;
 use std::collections::HashSet;
 
+use proc_macro2::{Delimiter, TokenStream as TokenStream2, TokenTree};
+use quote::quote;
 use syn::{
-    bracketed,
-    parse::{self, ParseStream},
+    bracketed, parenthesized,
+    parse::{self, ParseStream, Parser},
     punctuated::Punctuated,
     spanned::Spanned,
-    Abi, AttrStyle, Attribute, Expr, FnArg, ForeignItemFn, Ident, Item, ItemFn, ItemStatic, Pat,
-    PatType, PathArguments, ReturnType, Stmt, Token, Type, Visibility,
+    Abi, AttrStyle, Attribute, Expr, FnArg, Ident, Item, ItemStatic, Pat, PatType, PathArguments,
+    ReturnType, Stmt, Token, Type, Visibility,
 };
 
-use crate::{ast::Access, Map, Set};
+use crate::{ast::Access, InitReturnKind, Map, Set, Settings};
 
 pub fn abi_is_rust(abi: &Abi) -> bool {
     match &abi.name {
@@ -29,32 +32,189 @@ pub fn attr_eq(attr: &Attribute, name: &str) -> bool {
 ///
 /// - has no bounds (like where clauses)
 /// - is not `async`
-/// - is not `const`
 /// - is not `unsafe`
 /// - is not generic (has no type parameters)
 /// - is not variadic
-/// - uses the Rust ABI (and not e.g. "C")
-pub fn check_fn_signature(item: &ItemFn) -> bool {
-    item.vis == Visibility::Inherited
-        && item.sig.constness.is_none()
-        && item.sig.asyncness.is_none()
-        && item.sig.abi.is_none()
-        && item.sig.unsafety.is_none()
-        && item.sig.generics.params.is_empty()
-        && item.sig.generics.where_clause.is_none()
-        && item.sig.variadic.is_none()
+/// - has no visibility
+///
+/// `const` and explicit ABIs are rejected beforehand, with dedicated errors
+fn check_fn_signature(vis: &Visibility, sig: &syn::Signature) -> bool {
+    *vis == Visibility::Inherited
+        && sig.asyncness.is_none()
+        && sig.unsafety.is_none()
+        && sig.generics.params.is_empty()
+        && sig.generics.where_clause.is_none()
+        && sig.variadic.is_none()
+}
+
+/// The kinds of functions that make up an application
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ItemKind {
+    Init,
+    Idle,
+    HardwareTask,
+    SoftwareTask,
 }
 
-#[allow(dead_code)]
-pub fn check_foreign_fn_signature(item: &ForeignItemFn) -> bool {
-    item.vis == Visibility::Inherited
-        && item.sig.constness.is_none()
-        && item.sig.asyncness.is_none()
-        && item.sig.abi.is_none()
-        && item.sig.unsafety.is_none()
-        && item.sig.generics.params.is_empty()
-        && item.sig.generics.where_clause.is_none()
-        && item.sig.variadic.is_none()
+/// The inputs of a function whose signature has been validated
+pub struct Signature {
+    /// The pattern of the `Context` argument
+    pub context: Box<Pat>,
+    /// The remaining arguments; always empty unless the function is a software task
+    pub inputs: Vec<PatType>,
+}
+
+/// Checks the signature of an `#[init]`, `#[idle]` or `#[task]` function
+///
+/// Besides `check_fn_signature`, the function must take `#name::Context` as its first argument
+/// and return the type that its `kind` requires. Only software tasks may take more arguments.
+/// `const`s and ABIs get dedicated errors
+pub fn validate_signature(
+    vis: &Visibility,
+    sig: syn::Signature,
+    kind: ItemKind,
+    app: &Ident,
+    settings: &Settings,
+) -> parse::Result<Signature> {
+    if let Some(constness) = sig.constness {
+        return Err(parse::Error::new(
+            constness.span,
+            "RTIC task functions cannot be `const`",
+        ));
+    }
+
+    if let Some(abi) = &sig.abi {
+        return Err(parse::Error::new(
+            abi.extern_token.span,
+            "RTIC task functions cannot have an explicit ABI; the code generator controls the \
+             calling convention",
+        ));
+    }
+
+    let span = sig.ident.span();
+    let name = sig.ident.to_string();
+
+    let valid_output = match kind {
+        ItemKind::Init => type_is_init_return(&sig.output, &name, settings.init_return_type),
+        ItemKind::Idle => type_is_bottom(&sig.output),
+        ItemKind::HardwareTask | ItemKind::SoftwareTask => type_is_unit(&sig.output),
+    };
+
+    if check_fn_signature(vis, &sig) && valid_output {
+        if let Some((context, Ok(inputs))) = parse_inputs(sig.inputs, &name, app) {
+            if inputs.is_empty() || kind == ItemKind::SoftwareTask {
+                return Ok(Signature { context, inputs });
+            }
+        }
+    }
+
+    let message = match kind {
+        ItemKind::Init => {
+            let types = settings
+                .init_return_type
+                .types()
+                .iter()
+                .map(|ty| format!("{}::{}", name, ty))
+                .collect::<Vec<_>>();
+
+            let output = if types.len() == 1 {
+                types[0].clone()
+            } else {
+                format!("({})", types.join(", "))
+            };
+
+            format!(
+                "this `#[init]` function must have signature `fn({}::Context) -> {}`",
+                name, output
+            )
+        }
+        ItemKind::Idle => format!(
+            "this `#[idle]` function must have signature `fn({}::Context) -> !`",
+            name
+        ),
+        ItemKind::HardwareTask => format!(
+            "this task handler must have type signature `fn({}::Context)`",
+            name
+        ),
+        ItemKind::SoftwareTask => format!(
+            "this task handler must have type signature `fn({}::Context, ..)`",
+            name
+        ),
+    };
+
+    Err(parse::Error::new(span, message))
+}
+
+/// Removes every `#[name(..)]` attribute from `attrs` and merges their arguments
+///
+/// Returns `None` if there's no such attribute. An argument that appears in more than one of the
+/// attributes is reported at both of its locations
+pub fn extract_args(attrs: &mut Vec<Attribute>, name: &str) -> parse::Result<Option<TokenStream2>> {
+    let (matching, rest): (Vec<_>, Vec<_>) = attrs.drain(..).partition(|attr| attr_eq(attr, name));
+    *attrs = rest;
+
+    if matching.len() < 2 {
+        return Ok(matching.into_iter().next().map(|attr| attr.tokens));
+    }
+
+    let mut seen = Vec::<Ident>::new();
+    let mut merged = TokenStream2::new();
+    let mut trailing_comma = true;
+    for attr in matching {
+        let args = (|input: ParseStream<'_>| -> parse::Result<TokenStream2> {
+            if input.is_empty() {
+                return Ok(TokenStream2::new());
+            }
+
+            let content;
+            parenthesized!(content in input);
+            content.parse()
+        })
+        .parse2(attr.tokens)?;
+
+        if args.is_empty() {
+            continue;
+        }
+
+        // #ident = .., #ident = ..
+        let mut keys = vec![];
+        let mut at_key = true;
+        for tt in args.clone() {
+            if let TokenTree::Punct(punct) = &tt {
+                if punct.as_char() == ',' {
+                    at_key = true;
+                    continue;
+                }
+            }
+
+            if at_key {
+                if let TokenTree::Ident(ident) = tt {
+                    if let Some(first) = seen.iter().find(|first| **first == ident) {
+                        let mut err =
+                            parse::Error::new(ident.span(), "argument appears more than once");
+                        err.combine(parse::Error::new(
+                            first.span(),
+                            "argument first appears here",
+                        ));
+                        return Err(err);
+                    }
+
+                    keys.push(ident);
+                }
+            }
+
+            at_key = false;
+        }
+        seen.extend(keys);
+
+        if !trailing_comma {
+            merged.extend(quote!(,));
+        }
+        merged.extend(args);
+        trailing_comma = at_key;
+    }
+
+    Ok(Some(quote!((#merged))))
 }
 
 pub fn extract_cfgs(attrs: Vec<Attribute>) -> (Vec<Attribute>, Vec<Attribute>) {
@@ -72,6 +232,60 @@ pub fn extract_cfgs(attrs: Vec<Attribute>) -> (Vec<Attribute>, Vec<Attribute>) {
     (cfgs, not_cfgs)
 }
 
+/// Whether two `#[cfg]` lists can never be enabled at the same time
+///
+/// This is the case when one list contains `#[cfg(not(x))]` and the other `#[cfg(x)]`, or when
+/// `evaluate` reports a `#[cfg]` of either list as disabled
+pub fn cfgs_disjoint(
+    a: &[&Attribute],
+    b: &[&Attribute],
+    evaluate: Option<fn(&Attribute) -> bool>,
+) -> bool {
+    // `x` is `#[cfg(not(y))]` where `y` is the predicate of `#[cfg(y)]`
+    fn negates(x: &Attribute, y: &Attribute) -> bool {
+        match (
+            x.parse_args::<TokenStream2>(),
+            y.parse_args::<TokenStream2>(),
+        ) {
+            (Ok(x), Ok(y)) => x.to_string() == quote!(not(#y)).to_string(),
+            _ => false,
+        }
+    }
+
+    if let Some(evaluate) = evaluate {
+        if !a.iter().all(|attr| evaluate(attr)) || !b.iter().all(|attr| evaluate(attr)) {
+            return true;
+        }
+    }
+
+    a.iter()
+        .any(|x| b.iter().any(|y| negates(x, y) || negates(y, x)))
+}
+
+/// Whether a task with attributes `attrs` may share its name with an already parsed task of the
+/// other kind (hardware vs software) that has `other_cfgs`
+pub fn is_cfg_alternative(
+    attrs: &[Attribute],
+    other_cfgs: Option<&[Attribute]>,
+    settings: &Settings,
+) -> bool {
+    if !settings.allow_name_collisions_across_cfg {
+        return false;
+    }
+
+    if let Some(other_cfgs) = other_cfgs {
+        let cfgs = attrs
+            .iter()
+            .filter(|attr| attr_eq(attr, "cfg"))
+            .collect::<Vec<_>>();
+        let other_cfgs = other_cfgs.iter().collect::<Vec<_>>();
+
+        cfgs_disjoint(&cfgs, &other_cfgs, settings.evaluate_cfg)
+    } else {
+        false
+    }
+}
+
 pub fn extract_locals(stmts: Vec<Stmt>) -> parse::Result<(Vec<ItemStatic>, Vec<Stmt>)> {
     let mut istmts = stmts.into_iter();
 
@@ -128,6 +342,72 @@ pub fn extract_lock_free(attrs: &mut Vec<Attribute>) -> parse::Result<bool> {
     }
 }
 
+/// Skips the value of an argument: everything up to the next `,` or the end of the input
+pub fn skip_argument_value(input: ParseStream<'_>) -> parse::Result<()> {
+    while !input.is_empty() && !input.peek(Token![,]) {
+        input.parse::<TokenTree>()?;
+    }
+
+    Ok(())
+}
+
+/// Returns the "unexpected argument" error for `ident`
+///
+/// If one of the `valid` argument names is close enough to `ident` it is suggested in the error.
+/// The `core` and `cores` arguments of multi-core applications get a dedicated message
+pub fn unexpected_argument(ident: &Ident, valid: &[&str]) -> parse::Error {
+    let name = ident.to_string();
+
+    // arguments of the multi-core version of the syntax
+    if name == "core" || name == "cores" {
+        return parse::Error::new(
+            ident.span(),
+            format!(
+                "this backend targets a single core; remove the `{}` argument",
+                name
+            ),
+        );
+    }
+
+    let suggestion = valid
+        .iter()
+        .map(|candidate| (edit_distance(&name, candidate), candidate))
+        .filter(|(distance, candidate)| *distance <= cmp::max(candidate.len(), 3) / 3)
+        .min_by_key(|(distance, _)| *distance);
+
+    if let Some((_, candidate)) = suggestion {
+        parse::Error::new(
+            ident.span(),
+            format!("unexpected argument; did you mean `{}`?", candidate),
+        )
+    } else {
+        parse::Error::new(ident.span(), "unexpected argument")
+    }
+}
+
+/// Levenshtein distance between `a` and `b`
+fn edit_distance(a: &str, b: &str) -> usize {
+    let b = b.chars().collect::<Vec<_>>();
+    let mut row = (0..=b.len()).collect::<Vec<_>>();
+
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+
+        for (j, cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == *cb {
+                diagonal
+            } else {
+                1 + cmp::min(diagonal, cmp::min(row[j], above))
+            };
+            diagonal = above;
+        }
+    }
+
+    row[b.len()]
+}
+
 pub fn parse_idents(content: ParseStream<'_>) -> parse::Result<Set<Ident>> {
     let inner;
     bracketed!(inner in content);
@@ -196,12 +476,16 @@ pub fn parse_resources(content: ParseStream<'_>) -> parse::Result<Map<Access>> {
 
 type ParseInputResult = Option<(Box<Pat>, Result<Vec<PatType>, FnArg>)>;
 
-pub fn parse_inputs(inputs: Punctuated<FnArg, Token![,]>, name: &str) -> ParseInputResult {
+pub fn parse_inputs(
+    inputs: Punctuated<FnArg, Token![,]>,
+    name: &str,
+    app: &Ident,
+) -> ParseInputResult {
     let mut inputs = inputs.into_iter();
 
     match inputs.next() {
         Some(FnArg::Typed(first)) => {
-            if type_is_path(&first.ty, &[name, "Context"]) {
+            if type_is_context(&first.ty, name, app) {
                 let rest = inputs
                     .map(|arg| match arg {
                         FnArg::Typed(arg) => Ok(arg),
@@ -227,28 +511,58 @@ pub fn type_is_bottom(ty: &ReturnType) -> bool {
     }
 }
 
-pub fn type_is_init_return(ty: &ReturnType, name: &str) -> Result<(), ()> {
+/// Whether `ty` is the return type that `kind` requires of the `#name` init function
+pub fn type_is_init_return(ty: &ReturnType, name: &str, kind: InitReturnKind) -> bool {
+    let types = kind.types();
+
     match ty {
-        ReturnType::Default => Err(()),
+        ReturnType::Default => false,
 
         ReturnType::Type(_, ty) => match &**ty {
-            Type::Tuple(t) => {
-                if t.elems.len() == 2 {
-                    if type_is_path(&t.elems[0], &[name, "LateResources"])
-                        && type_is_path(&t.elems[1], &[name, "Monotonics"])
-                    {
-                        return Ok(());
-                    }
-                }
-
-                Err(())
+            Type::Tuple(t) if types.len() > 1 => {
+                t.elems.len() == types.len()
+                    && t.elems
+                        .iter()
+                        .zip(types)
+                        .all(|(ty, last)| type_is_path(ty, &[name, *last]))
             }
 
-            _ => Err(()),
+            ty => types.len() == 1 && type_is_path(ty, &[name, types[0]]),
         },
     }
 }
 
+/// Whether `ty` is the `#name::Context` type
+///
+/// The path may be qualified with `self` or with `crate` followed by the name of the `#[app]`
+/// module, as in `crate::app::foo::Context`. The name of the `#[app]` module alone doesn't
+/// resolve from inside the module
+pub fn type_is_context(ty: &Type, name: &str, app: &Ident) -> bool {
+    match ty {
+        Type::Path(tpath) if tpath.qself.is_none() && tpath.path.leading_colon.is_none() => {
+            let segments = tpath
+                .path
+                .segments
+                .iter()
+                .map(|segment| &segment.ident)
+                .collect::<Vec<_>>();
+            let n = segments.len();
+
+            n >= 2
+                && *segments[n - 2] == name
+                && *segments[n - 1] == "Context"
+                && match &segments[..n - 2] {
+                    [] => true,
+                    [prefix] => *prefix == "self",
+                    [krate, prefix] => *krate == "crate" && *prefix == app,
+                    _ => false,
+                }
+        }
+
+        _ => false,
+    }
+}
+
 pub fn type_is_path(ty: &Type, segments: &[&str]) -> bool {
     match ty {
         Type::Path(tpath) if tpath.qself.is_none() => {
@@ -265,6 +579,108 @@ pub fn type_is_path(ty: &Type, segments: &[&str]) -> bool {
     }
 }
 
+/// Whether `stmts` provably never finish: the last statement is a `loop` that no `break`,
+/// `return` or `?` leaves
+///
+/// A `break` only leaves the loop when it's not inside a nested loop, or when it names a label
+/// other than the ones of the nested loops it's in. `return` and `?` are assumed to leave the
+/// function even inside closures
+pub fn stmts_diverge(stmts: &[Stmt]) -> bool {
+    // Whether `tokens` may leave the outer loop; `nested` is whether they are inside a nested loop
+    // and `labels` are the labels of the nested loops
+    fn may_leave(tokens: TokenStream2, nested: bool, labels: &[String]) -> bool {
+        let tokens = tokens.into_iter().collect::<Vec<_>>();
+        // the label of the next loop, and whether the next `{ .. }` is a loop body
+        let mut label = None;
+        let mut in_loop_header = false;
+
+        for (i, tt) in tokens.iter().enumerate() {
+            match tt {
+                TokenTree::Ident(ident) if ident == "return" => return true,
+
+                TokenTree::Punct(punct) if punct.as_char() == '?' => return true,
+
+                TokenTree::Ident(ident) if ident == "break" => {
+                    let target = match (tokens.get(i + 1), tokens.get(i + 2)) {
+                        (Some(TokenTree::Punct(tick)), Some(TokenTree::Ident(name)))
+                            if tick.as_char() == '\'' =>
+                        {
+                            Some(name.to_string())
+                        }
+                        _ => None,
+                    };
+
+                    let leaves = match target {
+                        Some(target) => !labels.contains(&target),
+                        None => !nested,
+                    };
+                    if leaves {
+                        return true;
+                    }
+                }
+
+                TokenTree::Ident(ident)
+                    if ident == "loop"
+                        || ident == "while"
+                        || (ident == "for"
+                            && match tokens.get(i + 1) {
+                                Some(TokenTree::Punct(punct)) => punct.as_char() != '<',
+                                _ => true,
+                            }) =>
+                {
+                    in_loop_header = true;
+
+                    // `'label: loop`
+                    if i >= 3 {
+                        if let (
+                            TokenTree::Punct(tick),
+                            TokenTree::Ident(name),
+                            TokenTree::Punct(colon),
+                        ) = (&tokens[i - 3], &tokens[i - 2], &tokens[i - 1])
+                        {
+                            if tick.as_char() == '\'' && colon.as_char() == ':' {
+                                label = Some(name.to_string());
+                            }
+                        }
+                    }
+                }
+
+                TokenTree::Group(group) => {
+                    let leaves = if in_loop_header && group.delimiter() == Delimiter::Brace {
+                        in_loop_header = false;
+
+                        let mut labels = labels.to_vec();
+                        labels.extend(label.take());
+                        may_leave(group.stream(), true, &labels)
+                    } else {
+                        may_leave(group.stream(), nested, labels)
+                    };
+
+                    if leaves {
+                        return true;
+                    }
+                }
+
+                _ => {}
+            }
+        }
+
+        false
+    }
+
+    let expr = match stmts.last() {
+        Some(Stmt::Expr(expr)) | Some(Stmt::Semi(expr, _)) => expr,
+        _ => return false,
+    };
+
+    if let Expr::Loop(loop_) = expr {
+        let body = &loop_.body;
+        !may_leave(quote!(#body), false, &[])
+    } else {
+        false
+    }
+}
+
 pub fn type_is_unit(ty: &ReturnType) -> bool {
     if let ReturnType::Type(_, ty) = ty {
         if let Type::Tuple(ref tuple) = **ty {
@@ -276,3 +692,242 @@ pub fn type_is_unit(ty: &ReturnType) -> bool {
         true
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use quote::format_ident;
+    use syn::{parse_quote, ItemFn};
+
+    use super::ItemKind;
+    use crate::{InitReturnKind, Settings};
+
+    fn validate(item: ItemFn, kind: ItemKind) -> Result<usize, String> {
+        validate_with(item, kind, &Settings::default())
+    }
+
+    fn validate_with(item: ItemFn, kind: ItemKind, settings: &Settings) -> Result<usize, String> {
+        super::validate_signature(&item.vis, item.sig, kind, &format_ident!("app"), settings)
+            .map(|signature| signature.inputs.len())
+            .map_err(|e| e.to_string())
+    }
+
+    #[test]
+    fn init_signature() {
+        let ok: ItemFn = parse_quote!(
+            fn init(_: init::Context) -> (init::LateResources, init::Monotonics) {}
+        );
+        assert_eq!(validate(ok, ItemKind::Init), Ok(0));
+
+        let err: ItemFn = parse_quote!(
+            fn init(_: init::Context) {}
+        );
+        assert_eq!(
+            validate(err, ItemKind::Init),
+            Err("this `#[init]` function must have signature `fn(init::Context) -> (init::LateResources, init::Monotonics)`".to_string())
+        );
+    }
+
+    #[test]
+    fn idle_signature() {
+        let ok: ItemFn = parse_quote!(
+            fn idle(_: idle::Context) -> ! {}
+        );
+        assert_eq!(validate(ok, ItemKind::Idle), Ok(0));
+
+        let err: ItemFn = parse_quote!(
+            pub fn idle(_: idle::Context) -> ! {}
+        );
+        assert_eq!(
+            validate(err, ItemKind::Idle),
+            Err("this `#[idle]` function must have signature `fn(idle::Context) -> !`".to_string())
+        );
+    }
+
+    #[test]
+    fn idle_diverges() {
+        let stmts = |item: ItemFn| item.block.stmts;
+
+        assert!(super::stmts_diverge(&stmts(parse_quote!(
+            fn idle() {
+                init_leds();
+                loop {}
+            }
+        ))));
+        assert!(super::stmts_diverge(&stmts(parse_quote!(
+            fn idle() {
+                loop {
+                    for _ in 0..10 {}
+                }
+            }
+        ))));
+        assert!(!super::stmts_diverge(&stmts(parse_quote!(
+            fn idle() {
+                loop {
+                    if done() {
+                        break;
+                    }
+                }
+            }
+        ))));
+        assert!(!super::stmts_diverge(&stmts(parse_quote!(
+            fn idle() {
+                loop {}
+                cleanup();
+            }
+        ))));
+        assert!(!super::stmts_diverge(&stmts(parse_quote!(
+            fn idle() {
+                loop {
+                    return;
+                }
+            }
+        ))));
+        assert!(!super::stmts_diverge(&stmts(parse_quote!(
+            fn idle() {
+                loop {
+                    poll()?;
+                }
+            }
+        ))));
+        assert!(!super::stmts_diverge(&stmts(parse_quote!(
+            fn idle() {
+                'outer: loop {
+                    while busy() {
+                        break 'outer;
+                    }
+                }
+            }
+        ))));
+
+        // `break`s that only leave a nested loop
+        assert!(super::stmts_diverge(&stmts(parse_quote!(
+            fn idle() {
+                loop {
+                    loop {
+                        break;
+                    }
+                }
+            }
+        ))));
+        assert!(super::stmts_diverge(&stmts(parse_quote!(
+            fn idle() {
+                loop {
+                    'inner: for _ in 0..10 {
+                        loop {
+                            break 'inner;
+                        }
+                    }
+                }
+            }
+        ))));
+        assert!(!super::stmts_diverge(&stmts(parse_quote!(
+            fn idle() {}
+        ))));
+    }
+
+    #[test]
+    fn hardware_task_signature() {
+        let ok: ItemFn = parse_quote!(
+            fn foo(_: foo::Context) {}
+        );
+        assert_eq!(validate(ok, ItemKind::HardwareTask), Ok(0));
+
+        let err: ItemFn = parse_quote!(
+            fn foo(_: foo::Context, _: u32) {}
+        );
+        assert_eq!(
+            validate(err, ItemKind::HardwareTask),
+            Err("this task handler must have type signature `fn(foo::Context)`".to_string())
+        );
+    }
+
+    #[test]
+    fn software_task_signature() {
+        let ok: ItemFn = parse_quote!(
+            fn foo(_: foo::Context, _: u32, _: i32) {}
+        );
+        assert_eq!(validate(ok, ItemKind::SoftwareTask), Ok(2));
+
+        let err: ItemFn = parse_quote!(
+            unsafe fn foo(_: foo::Context) {}
+        );
+        assert_eq!(
+            validate(err, ItemKind::SoftwareTask),
+            Err("this task handler must have type signature `fn(foo::Context, ..)`".to_string())
+        );
+    }
+
+    #[test]
+    fn qualifiers() {
+        let kinds = [
+            (
+                ItemKind::Init,
+                "init",
+                " -> (init::LateResources, init::Monotonics)",
+            ),
+            (ItemKind::Idle, "idle", " -> !"),
+            (ItemKind::HardwareTask, "foo", ""),
+            (ItemKind::SoftwareTask, "foo", ""),
+        ];
+
+        // the code generator calls these functions itself so none of them takes a qualifier
+        for (kind, name, output) in kinds.iter().cloned() {
+            for qualifier in ["", "pub", "unsafe", "async", "const", "extern \"C\""].iter() {
+                let item: ItemFn = syn::parse_str(&format!(
+                    "{} fn {}(_: {}::Context){} {{}}",
+                    qualifier, name, name, output
+                ))
+                .unwrap();
+
+                let result = super::validate_signature(
+                    &item.vis,
+                    item.sig,
+                    kind,
+                    &format_ident!("app"),
+                    &Settings::default(),
+                );
+
+                assert_eq!(
+                    result.is_ok(),
+                    qualifier.is_empty(),
+                    "`{}` on {:?}",
+                    qualifier,
+                    kind
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn init_return_types() {
+        let late: ItemFn = parse_quote!(
+            fn init(_: init::Context) -> init::LateResources {}
+        );
+        let shared_local: ItemFn = parse_quote!(
+            fn init(_: init::Context) -> (init::Shared, init::Local) {}
+        );
+
+        for (kind, ok, err) in [
+            (InitReturnKind::LateResources, &late, &shared_local),
+            (InitReturnKind::SharedAndLocal, &shared_local, &late),
+        ]
+        .iter()
+        {
+            let mut settings = Settings::default();
+            settings.init_return_type = *kind;
+
+            assert_eq!(
+                validate_with((*ok).clone(), ItemKind::Init, &settings),
+                Ok(0)
+            );
+            assert!(validate_with((*err).clone(), ItemKind::Init, &settings).is_err());
+        }
+
+        let mut settings = Settings::default();
+        settings.init_return_type = InitReturnKind::SharedAndLocal;
+        assert_eq!(
+            validate_with(late, ItemKind::Init, &settings),
+            Err("this `#[init]` function must have signature `fn(init::Context) -> (init::Shared, init::Local)`".to_string())
+        );
+    }
+}
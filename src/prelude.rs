@@ -0,0 +1,25 @@
+//! The items that backends commonly need, in one place
+//!
+//! ```
+//! use rtic_syntax::prelude::*;
+//! ```
+//!
+//! Everything re-exported here is kept available through this module even when it moves around
+//! in the rest of the crate
+
+#[doc(no_inline)]
+pub use crate::{
+    analyze::{
+        Analysis, Channel, Channels, ContextLayout, ContextLayouts, CsStrategy, Dispatchers,
+        InterruptUse, Interrupts, LateResources, LatencyHint, Location, Locations, Ownership,
+        Ownerships, Priority, PriorityUsage, PriorityUse, ResourceUser, ResourceUsers, SendTypes,
+        SyncTypes, TypeOrigin,
+    },
+    app_from_tokens,
+    ast::{
+        Access, App, AppArgs, AppParts, HardwareTask, HardwareTaskArgs, Idle, IdleArgs, Init,
+        InitArgs, LateResource, Local, Monotonic, MonotonicArgs, Resource, SoftwareTask,
+        SoftwareTaskArgs,
+    },
+    parse, parse2, Context, Diagnostic, EffectivePriority, InitReturnKind, Map, Set, Settings, P,
+};
@@ -1,6 +1,10 @@
 use quote::quote;
 
-use crate::{analyze::Ownership, Settings};
+use crate::{
+    analyze::{ContextLayout, CsStrategy, Ownership, TypeOrigin},
+    ast::Access,
+    Settings,
+};
 
 #[test]
 fn unused_resource() {
@@ -59,7 +63,7 @@ fn resource_owned() {
     )
     .unwrap();
 
-    let (res, ownership) = analysis.ownerships.iter().next().unwrap();
+    let (res, ownership) = analysis.ownerships().iter().next().unwrap();
     assert_eq!(res.to_string(), "x");
     assert_eq!(*ownership, Ownership::Owned { priority: 1 });
 }
@@ -87,7 +91,7 @@ fn resource_coowned() {
     )
     .unwrap();
 
-    let (res, ownership) = analysis.ownerships.iter().next().unwrap();
+    let (res, ownership) = analysis.ownerships().iter().next().unwrap();
     assert_eq!(res.to_string(), "x");
     assert_eq!(*ownership, Ownership::CoOwned { priority: 1 });
 }
@@ -115,7 +119,7 @@ fn resource_contended() {
     )
     .unwrap();
 
-    let (res, ownership) = analysis.ownerships.iter().next().unwrap();
+    let (res, ownership) = analysis.ownerships().iter().next().unwrap();
     assert_eq!(res.to_string(), "x");
     assert_eq!(*ownership, Ownership::Contended { ceiling: 2 });
 }
@@ -306,6 +310,2569 @@ fn late_resources() {
     )
     .unwrap();
 
-    let late = &analysis.late_resources;
+    let late = &analysis.late_resource_assignment;
     assert_eq!(late.len(), 1);
 }
+
+#[test]
+fn canonicalize() {
+    // the same application written in two different orders
+    let mut a = crate::parse::app(
+        quote!(),
+        quote!(
+            mod app {
+                #[resources]
+                struct Resources {
+                    #[init(0)]
+                    x: i32,
+                    #[init(0)]
+                    y: i32,
+                }
+
+                #[task(priority = 2, capacity = 4, resources = [y, x])]
+                fn foo(_: foo::Context) {}
+
+                #[task(resources = [&x])]
+                fn bar(_: bar::Context) {}
+            }
+        ),
+        &mut Settings::default(),
+    )
+    .unwrap();
+
+    let mut b = crate::parse::app(
+        quote!(),
+        quote!(
+            mod app {
+                #[resources]
+                struct Resources {
+                    #[init(0)]
+                    y: i32,
+                    #[init(0)]
+                    x: i32,
+                }
+
+                #[task(resources = [&x])]
+                fn bar(_: bar::Context) {}
+
+                #[task(capacity = 4, resources = [x, y], priority = 2)]
+                fn foo(_: foo::Context) {}
+            }
+        ),
+        &mut Settings::default(),
+    )
+    .unwrap();
+
+    assert_ne!(format!("{:?}", a), format!("{:?}", b));
+
+    a.canonicalize();
+    b.canonicalize();
+
+    assert_eq!(format!("{:?}", a), format!("{:?}", b));
+
+    // default values written out or left implicit
+    let parse = |tasks: proc_macro2::TokenStream| {
+        let mut settings = Settings::default();
+        settings.parse_binds = true;
+
+        let mut app = crate::parse::app(
+            quote!(),
+            quote!(
+                mod app {
+                    #tasks
+                }
+            ),
+            &mut settings,
+        )
+        .unwrap();
+        app.canonicalize();
+
+        format!("{:?}", app)
+    };
+
+    assert_eq!(
+        parse(quote!(
+            #[task]
+            fn foo(_: foo::Context) {}
+
+            #[task(binds = UART0, shares_line_with = [B, A])]
+            fn bar(_: bar::Context) {}
+        )),
+        parse(quote!(
+            #[task(priority = 1, capacity = 1)]
+            fn foo(_: foo::Context) {}
+
+            #[task(priority = 1, shares_line_with = [A, B], binds = UART0)]
+            fn bar(_: bar::Context) {}
+        )),
+    );
+}
+
+#[test]
+fn argument_order() {
+    let (app, _analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[resources]
+                struct Resources {
+                    #[init(0)]
+                    x: i32,
+                }
+
+                #[task(resources = [x], capacity = 2, priority = 3)]
+                fn foo(_: foo::Context) {}
+            }
+        ),
+        Settings::default(),
+    )
+    .unwrap();
+
+    let foo = app.software_tasks.values().next().unwrap();
+    let order = foo.args.arg_order.iter().map(|ident| ident.to_string());
+    assert_eq!(
+        order.collect::<Vec<_>>(),
+        ["resources", "capacity", "priority"]
+    );
+}
+
+#[test]
+fn argument_typo() {
+    let err = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[resources]
+                struct Resources {
+                    #[init(0)]
+                    x: i32,
+                }
+
+                #[task(capacity = 2, priority = 3, resorces = [x], binds = UART0)]
+                fn foo(_: foo::Context) {}
+            }
+        ),
+        Settings::default(),
+    )
+    .err()
+    .unwrap();
+
+    assert_eq!(
+        err.to_string(),
+        "unexpected argument; did you mean `resources`?"
+    );
+}
+
+#[test]
+fn task_local_idle() {
+    // a task local resource owned by `idle` needs neither `Send` nor `Sync`
+    let (_app, analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[resources]
+                struct Resources {
+                    #[task_local]
+                    x: i32,
+                }
+
+                #[init]
+                fn init(_: init::Context) -> (init::LateResources, init::Monotonics) {
+                    ..
+                }
+
+                #[idle(resources = [x])]
+                fn idle(_: idle::Context) -> ! {
+                    loop {}
+                }
+            }
+        ),
+        Settings::default(),
+    )
+    .unwrap();
+
+    let (res, ownership) = analysis.ownerships().iter().next().unwrap();
+    assert_eq!(res.to_string(), "x");
+    assert_eq!(*ownership, Ownership::Owned { priority: 0 });
+    assert!(analysis.send_types.is_empty());
+    assert!(analysis.sync_types.is_empty());
+}
+
+#[test]
+fn dispatchers() {
+    // priorities are assigned to dispatchers in ascending order
+    let parse = || {
+        crate::parse2(
+            quote!(dispatchers = [A, B, C]),
+            quote!(
+                mod app {
+                    #[task(priority = 3)]
+                    fn foo(_: foo::Context) {}
+
+                    #[task]
+                    fn bar(_: bar::Context) {}
+                }
+            ),
+            Settings::default(),
+        )
+        .unwrap()
+    };
+
+    let (_app, analysis) = parse();
+    assert_eq!(analysis.dispatcher_for(1).unwrap().to_string(), "A");
+    assert_eq!(analysis.dispatcher_for(3).unwrap().to_string(), "B");
+    assert!(analysis.dispatcher_for(2).is_none());
+
+    let (_app, again) = parse();
+    assert_eq!(analysis.dispatchers(), again.dispatchers());
+}
+
+#[test]
+fn split_attributes() {
+    // arguments split across several attributes are merged
+    let (app, _analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[resources]
+                struct Resources {
+                    #[init(0)]
+                    x: i32,
+                }
+
+                #[task(resources = [x],)]
+                #[task]
+                #[task(priority = 2)]
+                fn foo(_: foo::Context) {}
+            }
+        ),
+        Settings::default(),
+    )
+    .unwrap();
+
+    let foo = app.software_tasks.values().next().unwrap();
+    assert!(foo.attrs.is_empty());
+    assert_eq!(foo.args.priority, 2);
+    assert!(foo.args.resources.keys().any(|name| *name == "x"));
+}
+
+#[test]
+fn unsafe_resource_initializer() {
+    // the initializer is kept verbatim, `unsafe` block included
+    let (app, _analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[resources]
+                struct Resources {
+                    #[init(unsafe { Peripheral::steal() })]
+                    x: Peripheral,
+                }
+            }
+        ),
+        Settings::default(),
+    )
+    .unwrap();
+
+    let expr = &app.resources.values().next().unwrap().expr;
+    assert_eq!(
+        quote!(#expr).to_string(),
+        quote!(unsafe { Peripheral::steal() }).to_string()
+    );
+}
+
+#[test]
+fn is_sound() {
+    let app = crate::parse::app(
+        quote!(dispatchers = [A, B]),
+        quote!(
+            mod app {
+                #[resources]
+                struct Resources {
+                    #[init(0)]
+                    x: i32,
+                }
+
+                #[task(resources = [x])]
+                fn foo(_: foo::Context) {}
+
+                #[task(priority = 2, resources = [x])]
+                fn bar(_: bar::Context) {}
+            }
+        ),
+        &mut Settings::default(),
+    )
+    .unwrap();
+    let mut analysis = crate::analyze::app(&app, &Settings::default()).unwrap();
+
+    assert!(analysis.is_sound(&app).is_ok());
+
+    // an empty channel and a ceiling below the priority of an accessor
+    analysis.channels_mut().insert(3, Default::default());
+    *analysis.ownerships_mut().values_mut().next().unwrap() = Ownership::Contended { ceiling: 1 };
+
+    assert_eq!(analysis.is_sound(&app).err().unwrap().len(), 2);
+}
+
+#[test]
+fn context_layouts() {
+    let mut settings = Settings::default();
+    settings.parse_binds = true;
+
+    let (_app, analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[resources]
+                struct Resources {
+                    #[init(0)]
+                    x: i32,
+                }
+
+                #[init]
+                fn init(_: init::Context) -> (init::LateResources, init::Monotonics) {
+                    static mut X: u32 = 0;
+                }
+
+                #[idle(resources = [x])]
+                fn idle(_: idle::Context) -> ! {
+                    loop {}
+                }
+
+                #[task(binds = UART0)]
+                fn foo(_: foo::Context) {
+                    static mut X: u32 = 0;
+                }
+
+                #[task(priority = 2, resources = [x])]
+                fn bar(_: bar::Context) {}
+            }
+        ),
+        settings,
+    )
+    .unwrap();
+
+    let layout = |name: &str| {
+        *analysis
+            .context_layouts
+            .iter()
+            .find(|(context, _)| *context == name)
+            .unwrap()
+            .1
+    };
+
+    assert_eq!(
+        layout("init"),
+        ContextLayout {
+            resources: false,
+            locals: true,
+            device_peripherals: true,
+            core_peripherals: true,
+            late_resources_return: true,
+        }
+    );
+    assert_eq!(
+        layout("idle"),
+        ContextLayout {
+            resources: true,
+            ..ContextLayout::default()
+        }
+    );
+    assert_eq!(
+        layout("foo"),
+        ContextLayout {
+            locals: true,
+            ..ContextLayout::default()
+        }
+    );
+    assert_eq!(
+        layout("bar"),
+        ContextLayout {
+            resources: true,
+            ..ContextLayout::default()
+        }
+    );
+}
+
+#[test]
+fn qualified_context() {
+    // the context type may be qualified with `self`, the name of the app module or its full path
+    let (app, _analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[init]
+                fn init(_: self::init::Context) -> (init::LateResources, init::Monotonics) {}
+
+                #[idle]
+                fn idle(_: crate::app::idle::Context) -> ! {
+                    loop {}
+                }
+
+                #[task]
+                fn foo(_: crate::app::foo::Context) {}
+
+                #[task]
+                fn bar(_: self::bar::Context, _: u32) {}
+            }
+        ),
+        Settings::default(),
+    )
+    .unwrap();
+
+    assert_eq!(app.software_tasks.len(), 2);
+
+    // prefixes that can't resolve to the context are rejected
+    for ty in vec![
+        quote!(crate::foo::Context),
+        quote!(app::foo::Context),
+        quote!(app::self::foo::Context),
+        quote!(self::crate::app::app::foo::Context),
+        quote!(app::app::foo::Context),
+    ] {
+        let result = crate::parse2(
+            quote!(),
+            quote!(
+                mod app {
+                    #[task]
+                    fn foo(_: #ty) {}
+                }
+            ),
+            Settings::default(),
+        );
+
+        assert!(result.is_err(), "`{}` was accepted", ty);
+    }
+}
+
+#[test]
+fn core_peripherals_user() {
+    let app = quote!(
+        mod app {
+            #[init]
+            fn init(_: init::Context) -> (init::LateResources, init::Monotonics) {}
+
+            #[idle(core_peripherals = true)]
+            fn idle(_: idle::Context) -> ! {
+                loop {}
+            }
+        }
+    );
+
+    // by default `init` takes the core peripherals
+    let (_app, analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[init]
+                fn init(_: init::Context) -> (init::LateResources, init::Monotonics) {}
+            }
+        ),
+        Settings::default(),
+    )
+    .unwrap();
+    assert_eq!(analysis.core_peripherals_user.unwrap().to_string(), "init");
+
+    // the argument is only accepted behind a setting
+    assert!(crate::parse2(quote!(), app.clone(), Settings::default()).is_err());
+
+    let mut settings = Settings::default();
+    settings.parse_core_peripherals = true;
+    let (_app, analysis) = crate::parse2(quote!(), app, settings).unwrap();
+
+    assert_eq!(analysis.core_peripherals_user.unwrap().to_string(), "idle");
+    assert!(
+        analysis
+            .context_layouts
+            .values()
+            .filter(|layout| layout.core_peripherals)
+            .count()
+            == 1
+    );
+}
+
+#[test]
+fn critical_section_strategy() {
+    let app = quote!(
+        mod app {
+            #[init]
+            fn init(_: init::Context) -> (init::LateResources, init::Monotonics) {}
+        }
+    );
+
+    let (_app, analysis) = crate::parse2(quote!(), app.clone(), Settings::default()).unwrap();
+    assert_eq!(analysis.critical_section_strategy, CsStrategy::Basepri);
+
+    // e.g. ARMv6-M
+    let mut settings = Settings::default();
+    settings.no_basepri = true;
+    let (_app, analysis) = crate::parse2(quote!(), app, settings).unwrap();
+    assert_eq!(analysis.critical_section_strategy, CsStrategy::MaskAll);
+}
+
+#[test]
+fn host_test_mode() {
+    let app = quote!(
+        mod app {
+            #[resources]
+            struct Resources {
+                #[init(0)]
+                x: u32,
+            }
+
+            #[task(binds = UART0, resources = [x])]
+            fn foo(_: foo::Context) {}
+
+            #[task(binds = UART1, priority = 2, resources = [x])]
+            fn bar(_: bar::Context) {}
+        }
+    );
+
+    // `UART1` is also used as a dispatcher, which is only a problem on the target
+    let mut settings = Settings::default();
+    settings.parse_binds = true;
+    assert!(crate::parse2(quote!(dispatchers = [UART1]), app.clone(), settings).is_err());
+
+    let mut settings = Settings::default();
+    settings.parse_binds = true;
+    settings.host_test_mode = true;
+    let (_app, analysis) = crate::parse2(quote!(dispatchers = [UART1]), app, settings).unwrap();
+
+    let (_, ownership) = analysis.ownerships().iter().next().unwrap();
+    assert_eq!(*ownership, Ownership::Contended { ceiling: 2 });
+
+    // the device peripherals are not required to come from a device
+    let app = quote!(
+        mod app {
+            #[task]
+            fn foo(_: foo::Context) {}
+        }
+    );
+
+    let args = quote!(peripherals = true, dispatchers = [UART0]);
+    assert!(crate::parse2(args.clone(), app.clone(), Settings::default()).is_err());
+
+    let mut settings = Settings::default();
+    settings.host_test_mode = true;
+    assert!(crate::parse2(args, app, settings).is_ok());
+
+    // nor are there interrupts to dispatch software tasks from
+    for args in vec![quote!(dispatchers = []), quote!(dispatchers = [UART0])] {
+        let app = quote!(
+            mod app {
+                #[task]
+                fn foo(_: foo::Context) {}
+
+                #[task(priority = 2)]
+                fn bar(_: bar::Context) {}
+            }
+        );
+
+        assert!(crate::parse2(args.clone(), app.clone(), Settings::default()).is_err());
+
+        let mut settings = Settings::default();
+        settings.host_test_mode = true;
+        assert!(crate::parse2(args, app, settings).is_ok());
+    }
+}
+
+#[test]
+fn diff() {
+    let (_app, old) = crate::parse2(
+        quote!(dispatchers = [UART0, UART1]),
+        quote!(
+            mod app {
+                #[resources]
+                struct Resources {
+                    #[init(0)]
+                    x: u32,
+                }
+
+                #[task(resources = [x])]
+                fn foo(_: foo::Context) {}
+
+                #[task(priority = 2)]
+                fn bar(_: bar::Context) {}
+            }
+        ),
+        Settings::default(),
+    )
+    .unwrap();
+
+    let (_app, new) = crate::parse2(
+        quote!(dispatchers = [UART0, UART1]),
+        quote!(
+            mod app {
+                #[resources]
+                struct Resources {
+                    #[init(0)]
+                    x: u32,
+                }
+
+                #[task(capacity = 2, resources = [x])]
+                fn foo(_: foo::Context) {}
+
+                #[task(priority = 2, resources = [x])]
+                fn bar(_: bar::Context, _: u32) {}
+            }
+        ),
+        Settings::default(),
+    )
+    .unwrap();
+
+    assert!(crate::analyze::diff(&old, &old).is_empty());
+
+    let diff = crate::analyze::diff(&old, &new);
+    assert_eq!(
+        diff.to_string(),
+        "resource `x`: owned at priority 1 -> ceiling 2\n\
+         capacity of priority 1 software tasks: 1 -> 2\n\
+         `u32` must now implement `Send`\n"
+    );
+}
+
+#[test]
+fn priority_consts() {
+    let app = quote!(
+        mod app {
+            #[task(priority = HIGH)]
+            fn foo(_: foo::Context) {}
+        }
+    );
+
+    let mut settings = Settings::default();
+    settings.priority_consts.insert("HIGH".to_string(), 7);
+    let (parsed, _analysis) = crate::parse2(quote!(), app.clone(), settings).unwrap();
+
+    assert_eq!(parsed.software_tasks[0].args.priority, 7);
+
+    // unknown names are rejected
+    let mut settings = Settings::default();
+    settings.priority_consts.insert("LOW".to_string(), 1);
+    let err = crate::parse2(quote!(), app, settings).err().unwrap();
+
+    assert_eq!(err.to_string(), "unknown priority constant");
+}
+
+#[test]
+fn explain_ceiling() {
+    let (app, analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[resources]
+                struct Resources {
+                    #[init(0)]
+                    x: u32,
+                    #[init(0)]
+                    y: u32,
+                }
+
+                #[task(priority = 2, resources = [x, y])]
+                fn foo(_: foo::Context) {}
+
+                #[task(priority = 5, resources = [x])]
+                fn bar(_: bar::Context) {}
+            }
+        ),
+        Settings::default(),
+    )
+    .unwrap();
+
+    let resource = |name: &str| app.resources.keys().find(|r| *r == name).unwrap();
+
+    assert_eq!(
+        analysis.explain_ceiling(resource("x")).unwrap(),
+        "ceiling 5 because `bar` accesses it at priority 5 and `foo` at priority 2"
+    );
+    assert_eq!(
+        analysis.explain_ceiling(resource("y")).unwrap(),
+        "no ceiling because only `foo` accesses it, at priority 2"
+    );
+}
+
+#[test]
+fn forward_references() {
+    // contexts may refer to resources (and `idle` may appear) before they are declared
+    let (app, analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[idle(resources = [x])]
+                fn idle(_: idle::Context) -> ! {
+                    loop {}
+                }
+
+                #[task(resources = [x])]
+                fn foo(_: foo::Context) {}
+
+                #[init]
+                fn init(_: init::Context) -> (init::LateResources, init::Monotonics) {}
+
+                #[resources]
+                struct Resources {
+                    x: u32,
+                }
+            }
+        ),
+        Settings::default(),
+    )
+    .unwrap();
+
+    assert_eq!(app.late_resources.len(), 1);
+    assert_eq!(
+        analysis.ownerships().values().next(),
+        Some(&Ownership::Contended { ceiling: 1 })
+    );
+}
+
+#[test]
+fn settings_gated_checks() {
+    let app = quote!(
+        mod app {
+            #[resources]
+            struct Resources {
+                #[init(0)]
+                x: u32,
+            }
+
+            #[idle(resources = [x])]
+            fn idle(_: idle::Context) -> ! {
+                loop {}
+            }
+        }
+    );
+
+    assert!(crate::parse2(quote!(), app.clone(), Settings::default()).is_ok());
+
+    let mut settings = Settings::default();
+    settings.idle_shared_access_only = true;
+    assert!(crate::parse2(quote!(), app, settings).is_err());
+}
+
+#[test]
+fn static_shared_resources() {
+    let (_app, analysis) = crate::parse2(
+        quote!(dispatchers = [UART0, UART1]),
+        quote!(
+            mod app {
+                #[resources]
+                struct Resources {
+                    #[init(0)]
+                    uncontended: u32,
+                    #[init(0)]
+                    read_only: u32,
+                    #[init(0)]
+                    contended: u32,
+                }
+
+                #[task(resources = [&uncontended, &read_only, contended])]
+                fn foo(_: foo::Context) {}
+
+                #[task(priority = 2, resources = [&read_only, contended])]
+                fn bar(_: bar::Context) {}
+            }
+        ),
+        Settings::default(),
+    )
+    .unwrap();
+
+    let names = analysis
+        .static_shared_resources
+        .iter()
+        .map(|name| name.to_string())
+        .collect::<Vec<_>>();
+
+    assert_eq!(names, ["read_only", "uncontended"]);
+}
+
+#[test]
+fn hardware_task_owned_resource() {
+    let mut settings = Settings::default();
+    settings.parse_binds = true;
+
+    let (_app, analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[resources]
+                struct Resources {
+                    #[init(0)]
+                    x: u64,
+                }
+
+                #[task(binds = UART0, priority = 3, resources = [x])]
+                fn foo(_: foo::Context) {}
+            }
+        ),
+        settings,
+    )
+    .unwrap();
+
+    let (name, ownership) = analysis.ownerships().iter().next().unwrap();
+    assert_eq!(name.to_string(), "x");
+    assert_eq!(*ownership, Ownership::Owned { priority: 3 });
+    assert!(!ownership.needs_lock(3));
+
+    assert!(analysis.send_types.is_empty());
+    assert!(analysis.sync_types.is_empty());
+}
+
+#[test]
+fn name_collisions_across_cfg() {
+    let app = quote!(
+        mod app {
+            #[cfg(feature = "uart")]
+            #[task(binds = UART0)]
+            fn foo(_: foo::Context) {}
+
+            #[cfg(not(feature = "uart"))]
+            #[task]
+            fn foo(_: foo::Context) {}
+        }
+    );
+
+    let mut settings = Settings::default();
+    settings.parse_binds = true;
+    assert!(crate::parse2(quote!(), app.clone(), settings).is_err());
+
+    let mut settings = Settings::default();
+    settings.parse_binds = true;
+    settings.allow_name_collisions_across_cfg = true;
+    let (parsed, _analysis) = crate::parse2(quote!(), app, settings).unwrap();
+
+    assert_eq!(parsed.hardware_tasks.len(), 1);
+    assert_eq!(parsed.software_tasks.len(), 1);
+
+    // cfgs that are not syntactically disjoint need a hook to tell them apart
+    let app = quote!(
+        mod app {
+            #[cfg(feature = "uart")]
+            #[task(binds = UART0)]
+            fn foo(_: foo::Context) {}
+
+            #[cfg(feature = "usb")]
+            #[task]
+            fn foo(_: foo::Context) {}
+        }
+    );
+
+    let mut settings = Settings::default();
+    settings.parse_binds = true;
+    settings.allow_name_collisions_across_cfg = true;
+    assert!(crate::parse2(quote!(), app.clone(), settings).is_err());
+
+    fn evaluate_cfg(attr: &syn::Attribute) -> bool {
+        quote!(#attr).to_string().contains("uart")
+    }
+
+    let mut settings = Settings::default();
+    settings.parse_binds = true;
+    settings.allow_name_collisions_across_cfg = true;
+    settings.evaluate_cfg = Some(evaluate_cfg);
+    assert!(crate::parse2(quote!(), app, settings).is_ok());
+}
+
+#[test]
+fn name_collisions_across_cfg_analysis() {
+    let mut settings = Settings::default();
+    settings.parse_binds = true;
+    settings.allow_name_collisions_across_cfg = true;
+
+    let (_app, analysis) = crate::parse2(
+        quote!(dispatchers = [A]),
+        quote!(
+            mod app {
+                #[resources]
+                struct Resources {
+                    #[init(0)]
+                    x: u32,
+                    #[init(0)]
+                    y: u32,
+                }
+
+                #[cfg(feature = "uart")]
+                #[task(binds = UART0, priority = 2, resources = [x])]
+                fn foo(_: foo::Context) {}
+
+                #[cfg(not(feature = "uart"))]
+                #[task(resources = [&y])]
+                fn foo(_: foo::Context) {
+                    static mut COUNT: u32 = 0;
+                }
+            }
+        ),
+        settings,
+    )
+    .unwrap();
+
+    let name = |name: &str| quote::format_ident!("{}", name);
+
+    assert_eq!(analysis.tasks, [name("foo")]);
+
+    assert_eq!(analysis.context_layouts.len(), 1);
+    assert_eq!(
+        analysis.context_layouts[&name("foo")],
+        ContextLayout {
+            resources: true,
+            locals: true,
+            ..ContextLayout::default()
+        }
+    );
+
+    let matrix = analysis.access_matrix();
+    assert_eq!(matrix.rows, [name("foo")]);
+    assert_eq!(
+        matrix.get(&name("foo"), &name("x")),
+        Some(Access::Exclusive)
+    );
+    assert_eq!(matrix.get(&name("foo"), &name("y")), Some(Access::Shared));
+
+    assert_eq!(
+        analysis.srp_model().tasks,
+        [(name("foo"), 1), (name("foo"), 2)]
+    );
+}
+
+#[test]
+fn read_only_resources() {
+    let (_app, analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[resources]
+                struct Resources {
+                    #[init(0)]
+                    shared: u32,
+                    #[init(0)]
+                    exclusive: u32,
+                    #[init(0)]
+                    written_by_init: u32,
+                }
+
+                #[init(resources = [written_by_init])]
+                fn init(_: init::Context) -> (init::LateResources, init::Monotonics) {}
+
+                #[idle(resources = [&shared, &written_by_init, exclusive])]
+                fn idle(_: idle::Context) -> ! {
+                    loop {}
+                }
+
+                #[task(resources = [&shared])]
+                fn foo(_: foo::Context) {}
+            }
+        ),
+        Settings::default(),
+    )
+    .unwrap();
+
+    let names = analysis
+        .read_only_resources
+        .iter()
+        .map(|name| name.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["shared"]);
+
+    // a late resource may be read-only unless the backend asks for an initial value
+    let app = quote!(
+        mod app {
+            #[resources]
+            struct Resources {
+                x: u32,
+            }
+
+            #[init]
+            fn init(_: init::Context) -> (init::LateResources, init::Monotonics) {}
+
+            #[task(resources = [&x])]
+            fn foo(_: foo::Context) {}
+        }
+    );
+
+    assert!(crate::parse2(quote!(), app.clone(), Settings::default()).is_ok());
+
+    let mut settings = Settings::default();
+    settings.reject_read_only_late_resources = true;
+    let err = crate::parse2(quote!(), app, settings).err().unwrap();
+    assert!(err.to_string().contains("`#[init(..)]`"));
+}
+
+#[test]
+fn priority_usage() {
+    let mut settings = Settings::default();
+    settings.parse_binds = true;
+
+    let (_app, analysis) = crate::parse2(
+        quote!(dispatchers = [SSI0]),
+        quote!(
+            mod app {
+                #[monotonic(binds = SysTick, priority = 2)]
+                type Fast = hal::SysTickMonotonic;
+
+                #[task(binds = UART0, priority = 2)]
+                fn foo(_: foo::Context) {}
+
+                #[task]
+                fn bar(_: bar::Context) {}
+            }
+        ),
+        settings,
+    )
+    .unwrap();
+
+    let usage = analysis.priority_usage();
+    assert_eq!(usage.keys().cloned().collect::<Vec<_>>(), [1, 2]);
+
+    let level1 = &usage[&1];
+    assert!(level1.hardware_tasks.is_empty());
+    assert_eq!(level1.software_tasks[0].to_string(), "bar");
+    assert_eq!(level1.dispatcher.as_ref().unwrap().to_string(), "SSI0");
+    assert!(level1.timer_queues.is_empty());
+
+    let level2 = &usage[&2];
+    assert_eq!(level2.hardware_tasks[0].to_string(), "foo");
+    assert!(level2.software_tasks.is_empty());
+    assert!(level2.dispatcher.is_none());
+    assert_eq!(level2.timer_queues[0].to_string(), "Fast");
+}
+
+#[test]
+fn priority_classes() {
+    let mut settings = Settings::default();
+    settings.parse_binds = true;
+
+    let (_app, analysis) = crate::parse2(
+        quote!(dispatchers = [SSI0]),
+        quote!(
+            mod app {
+                #[task(binds = UART0, priority = 2)]
+                fn foo(_: foo::Context) {}
+
+                #[task]
+                fn bar(_: bar::Context) {}
+
+                #[task(priority = 2)]
+                fn baz(_: baz::Context) {}
+            }
+        ),
+        settings,
+    )
+    .unwrap();
+
+    let classes = analysis
+        .priority_classes()
+        .into_iter()
+        .map(|(priority, tasks)| {
+            (
+                priority,
+                tasks
+                    .iter()
+                    .map(|task| task.to_string())
+                    .collect::<Vec<_>>(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        classes,
+        [
+            (1, vec!["bar".to_string()]),
+            (2, vec!["baz".to_string(), "foo".to_string()]),
+        ]
+    );
+}
+
+#[test]
+fn reentrancy() {
+    // parsing and analyzing one application doesn't affect another one
+    let bootloader = || {
+        crate::parse2(
+            quote!(dispatchers = [UART0, UART1]),
+            quote!(
+                mod bootloader {
+                    #[resources]
+                    struct Resources {
+                        #[init(0)]
+                        x: u32,
+                    }
+
+                    #[task(resources = [x])]
+                    fn foo(_: foo::Context, _: u8) {}
+
+                    #[task(priority = 2, resources = [x])]
+                    fn bar(_: bar::Context) {}
+                }
+            ),
+            Settings::default(),
+        )
+        .unwrap()
+    };
+
+    let application = || {
+        let mut settings = Settings::default();
+        settings.parse_binds = true;
+
+        crate::parse2(
+            quote!(dispatchers = [UART1, UART2]),
+            quote!(
+                mod application {
+                    #[resources]
+                    struct Resources {
+                        x: u64,
+                        #[init(0)]
+                        y: u32,
+                    }
+
+                    #[init]
+                    fn init(_: init::Context) -> (init::LateResources, init::Monotonics) {}
+
+                    #[task(binds = UART0, priority = 3, resources = [x, &y])]
+                    fn foo(_: foo::Context) {}
+
+                    #[task(capacity = 4, resources = [&y])]
+                    fn baz(_: baz::Context) {}
+                }
+            ),
+            settings,
+        )
+        .unwrap()
+    };
+
+    let (bootloader_app, bootloader_analysis) = bootloader();
+    let (application_app, application_analysis) = application();
+    let (interleaved_bootloader_app, interleaved_bootloader_analysis) = bootloader();
+    let (interleaved_application_app, interleaved_application_analysis) = application();
+
+    assert_eq!(
+        format!("{:?}", bootloader_app),
+        format!("{:?}", interleaved_bootloader_app)
+    );
+    assert_eq!(
+        format!("{:?}", application_app),
+        format!("{:?}", interleaved_application_app)
+    );
+    assert!(
+        crate::analyze::diff(&bootloader_analysis, &interleaved_bootloader_analysis).is_empty()
+    );
+    assert!(
+        crate::analyze::diff(&application_analysis, &interleaved_application_analysis).is_empty()
+    );
+    assert!(!crate::analyze::diff(&bootloader_analysis, &application_analysis).is_empty());
+}
+
+#[test]
+fn resource_attrs() {
+    // attributes like `#[link_section]` are kept so the backend can apply them to the static
+    let (app, _analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[resources]
+                struct Resources {
+                    #[link_section = ".ccmram"]
+                    #[init([0; 1024])]
+                    buffer: [u8; 1024],
+                    #[cfg(debug_assertions)]
+                    #[link_section = ".ccmram.late"]
+                    late: u32,
+                }
+
+                #[init]
+                fn init(_: init::Context) -> (init::LateResources, init::Monotonics) {}
+
+                #[task(resources = [buffer, late])]
+                fn foo(_: foo::Context) {}
+            }
+        ),
+        Settings::default(),
+    )
+    .unwrap();
+
+    let (_, buffer) = app.resources.iter().next().unwrap();
+    assert!(buffer.cfgs.is_empty());
+    assert_eq!(buffer.attrs.len(), 1);
+    assert!(buffer.attrs[0].path.is_ident("link_section"));
+    let attrs = &buffer.attrs;
+    assert_eq!(
+        quote!(#(#attrs)*).to_string(),
+        quote!(#[link_section = ".ccmram"]).to_string()
+    );
+
+    let (_, late) = app.late_resources.iter().next().unwrap();
+    assert_eq!(late.cfgs.len(), 1);
+    assert_eq!(late.attrs.len(), 1);
+    assert!(late.attrs[0].path.is_ident("link_section"));
+}
+
+#[test]
+fn metrics() {
+    let mut settings = Settings::default();
+    settings.parse_binds = true;
+
+    let (_app, analysis) = crate::parse2(
+        quote!(dispatchers = [SSI0, SSI1]),
+        quote!(
+            mod app {
+                #[resources]
+                struct Resources {
+                    #[init(0)]
+                    x: u32,
+                    #[init(0)]
+                    y: u32,
+                    #[init(0)]
+                    unused: u32,
+                    z: u64,
+                }
+
+                #[monotonic(binds = SysTick, priority = 3)]
+                type Fast = hal::SysTickMonotonic;
+
+                #[init]
+                fn init(_: init::Context) -> (init::LateResources, init::Monotonics) {}
+
+                #[task(binds = UART0, priority = 2, resources = [x, z])]
+                fn foo(_: foo::Context) {}
+
+                #[task(resources = [x, &y])]
+                fn bar(_: bar::Context) {}
+
+                #[task(priority = 2, capacity = 2, resources = [&y])]
+                fn baz(_: baz::Context, _: i16) {}
+            }
+        ),
+        settings,
+    )
+    .unwrap();
+
+    assert_eq!(
+        analysis.metrics().to_string(),
+        "resources=4\n\
+         accessed_resources=3\n\
+         contended_resources=2\n\
+         hardware_tasks=1\n\
+         software_tasks=2\n\
+         channels=2\n\
+         timer_queues=1\n\
+         send_types=2\n\
+         sync_types=1\n"
+    );
+}
+
+#[test]
+fn send_sync_types_order() {
+    let types = |analysis: &crate::analyze::Analysis| {
+        let send = analysis
+            .send_types
+            .iter()
+            .map(|ty| quote!(#ty).to_string())
+            .collect::<Vec<_>>();
+        let sync = analysis
+            .sync_types
+            .iter()
+            .map(|ty| quote!(#ty).to_string())
+            .collect::<Vec<_>>();
+
+        (send, sync)
+    };
+
+    let runs = (0..50)
+        .map(|_| {
+            let (_app, analysis) = crate::parse2(
+                quote!(dispatchers = [A, B]),
+                quote!(
+                    mod app {
+                        #[resources]
+                        struct Resources {
+                            z: u64,
+                            a: i8,
+                            #[init(0)]
+                            y: u16,
+                            #[init(false)]
+                            b: bool,
+                        }
+
+                        #[init]
+                        fn init(_: init::Context) -> (init::LateResources, init::Monotonics) {}
+
+                        #[task(resources = [z, a, &y, &b])]
+                        fn foo(_: foo::Context, _: u32, _: [u8; 4]) {}
+
+                        #[task(priority = 2, resources = [&y, &b])]
+                        fn bar(_: bar::Context, _: char) {}
+                    }
+                ),
+                Settings::default(),
+            )
+            .unwrap();
+
+            types(&analysis)
+        })
+        .collect::<Vec<_>>();
+
+    let (send, sync) = &runs[0];
+    assert_eq!(send, &["[u8 ; 4]", "char", "i8", "u32", "u64"]);
+    assert_eq!(sync, &["bool", "u16"]);
+    assert!(runs.iter().all(|run| run == &runs[0]));
+}
+
+#[test]
+fn init_cfg_stmts() {
+    // `#[cfg]`-ed statements in `init` reach the backend untouched
+    let (app, _analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[init]
+                fn init(_: init::Context) -> (init::LateResources, init::Monotonics) {
+                    static mut X: u32 = 0;
+
+                    #[cfg(debug_assertions)]
+                    let y = 1;
+
+                    #[cfg(feature = "x")]
+                    {
+                        setup();
+                    }
+
+                    (init::LateResources {}, init::Monotonics())
+                }
+            }
+        ),
+        Settings::default(),
+    )
+    .unwrap();
+
+    let init = &app.inits[0];
+    assert_eq!(init.locals.len(), 1);
+
+    let stmts = &init.stmts;
+    assert_eq!(stmts.len(), 3);
+    assert_eq!(
+        quote!(#(#stmts)*).to_string(),
+        quote!(
+            #[cfg(debug_assertions)]
+            let y = 1;
+
+            #[cfg(feature = "x")]
+            {
+                setup();
+            }
+
+            (init::LateResources {}, init::Monotonics())
+        )
+        .to_string()
+    );
+}
+
+#[test]
+fn prune_dead() {
+    let mut app = crate::parse::app(
+        quote!(dispatchers = [A]),
+        quote!(
+            mod app {
+                #[resources]
+                struct Resources {
+                    #[init(0)]
+                    x: u32,
+                    #[init(0)]
+                    dead: u32,
+                    y: u32,
+                    late_dead: u32,
+                }
+
+                #[init(late = [y, late_dead])]
+                fn init(_: init::Context) -> (init::LateResources, init::Monotonics) {}
+
+                #[task(resources = [x, y])]
+                fn foo(_: foo::Context) {}
+
+                #[task]
+                fn bar(_: bar::Context) {}
+            }
+        ),
+        &mut Settings::default(),
+    )
+    .unwrap();
+
+    let analysis = crate::analyze::app(&app, &Settings::default()).unwrap();
+    app.prune_dead(&analysis);
+
+    let names = |names: &mut dyn Iterator<Item = &syn::Ident>| {
+        names.map(|name| name.to_string()).collect::<Vec<_>>()
+    };
+    assert_eq!(names(&mut app.resources.keys()), ["x"]);
+    assert_eq!(names(&mut app.late_resources.keys()), ["y"]);
+    assert_eq!(names(&mut app.inits[0].args.late.iter()), ["y"]);
+    // software tasks are kept even if nothing spawns them
+    assert_eq!(names(&mut app.software_tasks.keys()), ["foo", "bar"]);
+
+    let pruned = crate::analyze::app(&app, &Settings::default()).unwrap();
+    assert!(crate::analyze::diff(&analysis, &pruned).is_empty());
+    assert_eq!(
+        format!("{:?}", analysis.locations),
+        format!("{:?}", pruned.locations)
+    );
+}
+
+#[test]
+fn late_resources_explicit() {
+    // an explicit `late` list is honored
+    let (_app, analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[resources]
+                struct Resources {
+                    x: i32,
+                    y: i32,
+                }
+
+                #[init(late = [y, x])]
+                fn init(_: init::Context) -> (init::LateResources, init::Monotonics) {}
+
+                #[task(resources = [x, y])]
+                fn foo(_: foo::Context) {}
+            }
+        ),
+        Settings::default(),
+    )
+    .unwrap();
+
+    let late = &analysis.late_resource_assignment;
+    assert_eq!(late.len(), 1);
+    assert_eq!(
+        late[0]
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>(),
+        ["x", "y"]
+    );
+}
+
+#[test]
+fn priority_explicit() {
+    let mut settings = Settings::default();
+    settings.parse_binds = true;
+
+    let (mut app, _analysis) = crate::parse2(
+        quote!(dispatchers = [A]),
+        quote!(
+            mod app {
+                #[task]
+                fn foo(_: foo::Context) {}
+
+                #[task(priority = 1)]
+                fn bar(_: bar::Context) {}
+
+                #[task(binds = UART0)]
+                fn baz(_: baz::Context) {}
+
+                #[task(binds = UART1, priority = 1)]
+                fn qux(_: qux::Context) {}
+            }
+        ),
+        settings,
+    )
+    .unwrap();
+
+    let foo = &app.software_tasks.get_index(0).unwrap().1.args;
+    assert_eq!(foo.priority, 1);
+    assert!(!foo.priority_explicit);
+
+    let bar = &app.software_tasks.get_index(1).unwrap().1.args;
+    assert_eq!(bar.priority, 1);
+    assert!(bar.priority_explicit);
+
+    let baz = &app.hardware_tasks.get_index(0).unwrap().1.args;
+    assert_eq!(baz.priority, 1);
+    assert!(!baz.priority_explicit);
+
+    let qux = &app.hardware_tasks.get_index(1).unwrap().1.args;
+    assert_eq!(qux.priority, 1);
+    assert!(qux.priority_explicit);
+
+    // a defaulted priority is as good as a written one once canonicalized
+    app.canonicalize();
+    assert!(app
+        .software_tasks
+        .values()
+        .all(|task| task.args.priority_explicit));
+    assert!(app
+        .hardware_tasks
+        .values()
+        .all(|task| task.args.priority_explicit));
+}
+
+#[test]
+fn pool() {
+    let (app, analysis) = crate::parse2(
+        quote!(dispatchers = [A]),
+        quote!(
+            mod app {
+                #[resources]
+                struct Resources {
+                    #[init(Pool::new())]
+                    P: Pool,
+                }
+
+                #[task(pool = P, capacity = 4)]
+                fn foo(_: foo::Context, _: Box<P>) {}
+
+                #[task]
+                fn bar(_: bar::Context) {}
+            }
+        ),
+        Settings::default(),
+    )
+    .unwrap();
+
+    let foo = &app.software_tasks.get_index(0).unwrap().1.args;
+    assert_eq!(foo.pool.as_ref().unwrap().to_string(), "P");
+    assert_eq!(foo.capacity, 4);
+    let bar = &app.software_tasks.get_index(1).unwrap().1.args;
+    assert!(bar.pool.is_none());
+
+    // the pool is live even though no context lists it
+    assert_eq!(analysis.locations.len(), 1);
+    assert!(analysis.ownerships().is_empty());
+}
+
+#[test]
+fn pre_init_masked_interrupts() {
+    let mut settings = Settings::default();
+    settings.parse_binds = true;
+
+    let (_app, analysis) = crate::parse2(
+        quote!(dispatchers = [SSI0, SSI1]),
+        quote!(
+            mod app {
+                #[resources]
+                struct Resources {
+                    x: u32,
+                    #[init(0)]
+                    y: u32,
+                }
+
+                #[init]
+                fn init(_: init::Context) -> (init::LateResources, init::Monotonics) {}
+
+                #[task(binds = UART0, resources = [x])]
+                fn foo(_: foo::Context) {}
+
+                #[task(binds = UART1, resources = [y])]
+                fn bar(_: bar::Context) {}
+
+                #[task(resources = [y])]
+                fn baz(_: baz::Context) {}
+
+                #[task(priority = 2, resources = [x])]
+                fn qux(_: qux::Context) {}
+            }
+        ),
+        settings,
+    )
+    .unwrap();
+
+    assert_eq!(
+        analysis
+            .pre_init_masked_interrupts
+            .iter()
+            .map(|interrupt| interrupt.to_string())
+            .collect::<Vec<_>>(),
+        ["SSI1", "UART0"]
+    );
+}
+
+#[test]
+fn device() {
+    let (app, _analysis) = crate::parse2(
+        quote!(device = stm32f4::pac, peripherals = true),
+        quote!(
+            mod app {}
+        ),
+        Settings::default(),
+    )
+    .unwrap();
+
+    let device = app.args.device.as_ref().unwrap();
+    assert_eq!(quote!(#device).to_string(), "stm32f4 :: pac");
+    assert!(app.args.peripherals);
+
+    // `peripherals` defaults to `true`, with or without a device
+    let (app, _analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {}
+        ),
+        Settings::default(),
+    )
+    .unwrap();
+    assert!(app.args.device.is_none());
+    assert!(app.args.peripherals);
+}
+
+#[test]
+fn writers_and_readers() {
+    let (_app, analysis) = crate::parse2(
+        quote!(dispatchers = [A, B]),
+        quote!(
+            mod app {
+                #[resources]
+                struct Resources {
+                    x: u32,
+                    #[init(0)]
+                    y: u32,
+                }
+
+                #[init(resources = [y])]
+                fn init(_: init::Context) -> (init::LateResources, init::Monotonics) {}
+
+                #[task(resources = [x, &y])]
+                fn foo(_: foo::Context) {}
+
+                #[task(priority = 2, resources = [&y])]
+                fn bar(_: bar::Context) {}
+            }
+        ),
+        Settings::default(),
+    )
+    .unwrap();
+
+    let names = |contexts: Vec<&syn::Ident>| {
+        contexts
+            .iter()
+            .map(|name| name.to_string())
+            .collect::<Vec<_>>()
+    };
+
+    let x = quote::format_ident!("x");
+    // `init` initializes the late resource `x`
+    assert_eq!(names(analysis.writers_of(&x)), ["init", "foo"]);
+    assert!(analysis.readers_of(&x).is_empty());
+
+    let y = quote::format_ident!("y");
+    assert_eq!(names(analysis.writers_of(&y)), ["init"]);
+    assert_eq!(names(analysis.readers_of(&y)), ["foo", "bar"]);
+
+    let z = quote::format_ident!("z");
+    assert!(analysis.writers_of(&z).is_empty());
+    assert!(analysis.readers_of(&z).is_empty());
+}
+
+#[test]
+fn lenient_idle_never() {
+    let idle = || {
+        quote!(
+            mod app {
+                #[idle]
+                fn idle(_: idle::Context) {
+                    loop {}
+                }
+            }
+        )
+    };
+
+    assert!(crate::parse2(quote!(), idle(), Settings::default()).is_err());
+
+    let mut settings = Settings::default();
+    settings.lenient_idle_never = true;
+    let (app, _analysis) = crate::parse2(quote!(), idle(), settings).unwrap();
+    assert_eq!(app.idles.len(), 1);
+
+    // the body must provably diverge
+    let mut settings = Settings::default();
+    settings.lenient_idle_never = true;
+    let result = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[idle]
+                fn idle(_: idle::Context) -> () {
+                    loop {
+                        break;
+                    }
+                }
+            }
+        ),
+        settings,
+    );
+    assert!(result.is_err());
+}
+
+#[test]
+fn access_matrix() {
+    let (_app, analysis) = crate::parse2(
+        quote!(dispatchers = [A, B]),
+        quote!(
+            mod app {
+                #[resources]
+                struct Resources {
+                    #[init(0)]
+                    x: u32,
+                    #[init(0)]
+                    y: u32,
+                    #[init(0)]
+                    z: u32,
+                }
+
+                #[task(resources = [&x, y])]
+                fn foo(_: foo::Context) {}
+
+                #[task(priority = 2, resources = [&x])]
+                fn bar(_: bar::Context) {}
+            }
+        ),
+        Settings::default(),
+    )
+    .unwrap();
+
+    let matrix = analysis.access_matrix();
+    let names = |names: &[syn::Ident]| names.iter().map(|n| n.to_string()).collect::<Vec<_>>();
+    assert_eq!(names(&matrix.rows), ["foo", "bar"]);
+    assert_eq!(names(&matrix.columns), ["x", "y", "z"]);
+
+    let (foo, bar) = (&matrix.rows[0], &matrix.rows[1]);
+    let (x, y, z) = (&matrix.columns[0], &matrix.columns[1], &matrix.columns[2]);
+    assert_eq!(matrix.get(foo, x), Some(Access::Shared));
+    assert_eq!(matrix.get(foo, y), Some(Access::Exclusive));
+    assert_eq!(matrix.get(foo, z), None);
+    assert_eq!(matrix.get(bar, x), Some(Access::Shared));
+    assert_eq!(matrix.get(bar, y), None);
+    assert_eq!(matrix.get(bar, &quote::format_ident!("w")), None);
+}
+
+#[test]
+fn srp_model() {
+    let mut settings = Settings::default();
+    settings.parse_binds = true;
+
+    let (_app, analysis) = crate::parse2(
+        quote!(dispatchers = [A, B]),
+        quote!(
+            mod app {
+                #[resources]
+                struct Resources {
+                    #[init(0)]
+                    x: u32,
+                    #[init(0)]
+                    y: u32,
+                }
+
+                #[task(binds = UART0, priority = 3, resources = [x])]
+                fn high(_: high::Context) {}
+
+                #[task(priority = 2, resources = [x, y])]
+                fn medium(_: medium::Context) {}
+
+                #[task(resources = [y])]
+                fn low(_: low::Context) {}
+            }
+        ),
+        settings,
+    )
+    .unwrap();
+
+    let model = analysis.srp_model();
+    let name = |name: &str| quote::format_ident!("{}", name);
+
+    assert_eq!(
+        model.tasks,
+        [(name("high"), 3), (name("medium"), 2), (name("low"), 1)]
+    );
+
+    let mut resources = model.resources.clone();
+    resources.sort();
+    assert_eq!(resources, [(name("x"), 3), (name("y"), 2)]);
+
+    let mut locks = model.locks.clone();
+    locks.sort();
+    assert_eq!(
+        locks,
+        [(name("low"), name("y")), (name("medium"), name("x"))]
+    );
+}
+
+#[test]
+fn effective_priority() {
+    use crate::{Context, EffectivePriority};
+
+    let mut settings = Settings::default();
+    settings.parse_binds = true;
+
+    let (app, _analysis) = crate::parse2(
+        quote!(dispatchers = [A]),
+        quote!(
+            mod app {
+                #[init]
+                fn init(_: init::Context) -> (init::LateResources, init::Monotonics) {}
+
+                #[idle]
+                fn idle(_: idle::Context) -> ! {
+                    loop {}
+                }
+
+                #[task(binds = UART0, priority = 3)]
+                fn foo(_: foo::Context) {}
+
+                #[task]
+                fn bar(_: bar::Context) {}
+            }
+        ),
+        settings,
+    )
+    .unwrap();
+
+    let foo = app.hardware_tasks.keys().next().unwrap();
+    let bar = app.software_tasks.keys().next().unwrap();
+    assert_eq!(
+        Context::Init.effective_priority(&app),
+        EffectivePriority::PreRuntime
+    );
+    assert_eq!(
+        Context::Idle.effective_priority(&app),
+        EffectivePriority::Thread
+    );
+    assert_eq!(
+        Context::HardwareTask(foo).effective_priority(&app),
+        EffectivePriority::Interrupt(3)
+    );
+    assert_eq!(
+        Context::SoftwareTask(bar).effective_priority(&app),
+        EffectivePriority::Interrupt(1)
+    );
+
+    // from lowest to highest
+    let table = [
+        (EffectivePriority::PreRuntime, None),
+        (EffectivePriority::Thread, Some(0)),
+        (EffectivePriority::Interrupt(1), Some(1)),
+        (EffectivePriority::Interrupt(2), Some(2)),
+        (EffectivePriority::Interrupt(255), Some(255)),
+    ];
+    for (i, (lower, number)) in table.iter().enumerate() {
+        assert_eq!(lower.priority(), *number);
+
+        for (higher, _) in &table[i + 1..] {
+            assert!(lower < higher);
+        }
+    }
+}
+
+#[test]
+fn summary() {
+    let mut settings = Settings::default();
+    settings.parse_binds = true;
+
+    let (app, analysis) = crate::parse2(
+        quote!(dispatchers = [SSI0]),
+        quote!(
+            mod app {
+                #[resources]
+                struct Resources {
+                    #[init(0)]
+                    x: u32,
+                }
+
+                #[task(binds = UART0, priority = 2, resources = [x])]
+                fn foo(_: foo::Context) {}
+
+                #[task(resources = [x])]
+                fn bar(_: bar::Context) {}
+            }
+        ),
+        settings,
+    )
+    .unwrap();
+
+    let mut streamed = vec![];
+    analysis.write_summary(&mut streamed, &app).unwrap();
+
+    let summary = analysis.summary(&app);
+    assert_eq!(String::from_utf8(streamed).unwrap(), summary);
+    assert_eq!(
+        summary,
+        "priority 1: software tasks `bar`; dispatcher `SSI0`\n\
+         priority 2: hardware tasks `foo`\n\
+         resource `x: u32`: ceiling 2 because `foo` accesses it at priority 2 and `bar` at \
+         priority 1\n\
+         resources=1\n\
+         accessed_resources=1\n\
+         contended_resources=1\n\
+         hardware_tasks=1\n\
+         software_tasks=1\n\
+         channels=1\n\
+         timer_queues=0\n\
+         send_types=0\n\
+         sync_types=0\n"
+    );
+}
+
+#[test]
+fn dispatcher_bounds() {
+    // `bar` is the only task at priority 2; the application needs a second dispatcher only when
+    // `bar` is compiled in
+    let app = quote!(
+        mod app {
+            #[task]
+            fn foo(_: foo::Context) {}
+
+            #[cfg(feature = "bar")]
+            #[task(priority = 2)]
+            fn bar(_: bar::Context) {}
+        }
+    );
+
+    let (_app, analysis) = crate::parse2(
+        quote!(dispatchers = [A, B]),
+        app.clone(),
+        Settings::default(),
+    )
+    .unwrap();
+    assert_eq!(analysis.dispatcher_bounds.lower, 1);
+    assert_eq!(analysis.dispatcher_bounds.upper, 2);
+
+    // checked against the upper bound by default
+    assert!(crate::parse2(quote!(dispatchers = [A]), app.clone(), Settings::default()).is_err());
+
+    let mut settings = Settings::default();
+    settings.dispatcher_check_lower_bound = true;
+    let (_app, analysis) = crate::parse2(quote!(dispatchers = [A]), app, settings).unwrap();
+    assert_eq!(analysis.dispatchers().len(), 1);
+}
+
+#[test]
+fn app_from_tokens() {
+    let app = quote!(
+        mod app {
+            #[task(priorty = 2)]
+            fn foo(_: foo::Context) {}
+
+            #[task(capacity = 2)]
+            fn bar(_: bar::Context) {}
+        }
+    );
+    // misspelled arguments are reported and ignored; the rest of the application is parsed
+    let (parsed, _analysis, diagnostics) = crate::app_from_tokens(
+        quote!(dispatchers = [A], devise = lm3s6965),
+        app.clone(),
+        Settings::default(),
+    )
+    .unwrap();
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(
+        diagnostics[0].to_string(),
+        "unexpected argument; did you mean `device`?"
+    );
+    assert_eq!(
+        diagnostics[1].to_string(),
+        "unexpected argument; did you mean `priority`?"
+    );
+    assert!(parsed.args.device.is_none());
+    assert_eq!(parsed.software_tasks[0].args.priority, 1);
+    assert!(parsed.software_tasks[0].args.arg_order.is_empty());
+    assert_eq!(parsed.software_tasks[1].args.capacity, 2);
+
+    // `parse2` stops at the first problem
+    assert_eq!(
+        crate::parse2(quote!(dispatchers = [A]), app.clone(), Settings::default())
+            .unwrap_err()
+            .to_string(),
+        "unexpected argument; did you mean `priority`?"
+    );
+
+    // structural problems abort but still come after the recoverable ones
+    let diagnostics = crate::app_from_tokens(
+        quote!(dispatchers = [A]),
+        quote!(
+            mod app {
+                #[task(priorty = 2)]
+                fn foo(_: foo::Context) {}
+
+                fn bar() {}
+            }
+        ),
+        Settings::default(),
+    )
+    .unwrap_err();
+    assert_eq!(diagnostics.len(), 2);
+    assert_eq!(
+        diagnostics[1].to_string(),
+        "this item must live outside the `#[app]` module"
+    );
+
+    // also in `#[monotonic]`
+    let (parsed, _analysis, diagnostics) = crate::app_from_tokens(
+        quote!(),
+        quote!(
+            mod app {
+                #[monotonic(binds = SysTick, prority = 2)]
+                type Fast = hal::SysTickMonotonic;
+            }
+        ),
+        Settings::default(),
+    )
+    .unwrap();
+    assert_eq!(diagnostics.len(), 1);
+    assert_eq!(
+        diagnostics[0].to_string(),
+        "unexpected argument; did you mean `priority`?"
+    );
+    let monotonic = &parsed.monotonics[0];
+    assert_eq!(monotonic.args.binds.to_string(), "SysTick");
+    assert_eq!(monotonic.args.arg_order.len(), 1);
+}
+
+#[test]
+fn binds_across_cfg() {
+    // tasks that are never compiled in together may bind the same interrupt
+    let mut settings = Settings::default();
+    settings.parse_binds = true;
+    let (parsed, _analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[cfg(feature = "a")]
+                #[task(binds = UART0)]
+                fn foo(_: foo::Context) {}
+
+                #[cfg(not(feature = "a"))]
+                #[task(binds = UART0, priority = 2)]
+                fn bar(_: bar::Context) {}
+            }
+        ),
+        settings,
+    )
+    .unwrap();
+    assert_eq!(parsed.hardware_tasks.len(), 2);
+
+    let app = quote!(
+        mod app {
+            #[cfg(feature = "a")]
+            #[task(binds = UART0)]
+            fn foo(_: foo::Context) {}
+
+            #[cfg(feature = "b")]
+            #[task(binds = UART0)]
+            fn bar(_: bar::Context) {}
+        }
+    );
+
+    // `a` and `b` may be enabled at the same time
+    let mut settings = Settings::default();
+    settings.parse_binds = true;
+    assert_eq!(
+        crate::parse2(quote!(), app.clone(), settings)
+            .unwrap_err()
+            .to_string(),
+        "this interrupt is already bound"
+    );
+
+    fn evaluate_cfg(attr: &syn::Attribute) -> bool {
+        quote!(#attr).to_string().contains("\"a\"")
+    }
+
+    let mut settings = Settings::default();
+    settings.parse_binds = true;
+    settings.evaluate_cfg = Some(evaluate_cfg);
+    assert!(crate::parse2(quote!(), app, settings).is_ok());
+
+    // the same `#[cfg]`s don't make the interrupt available again
+    let mut settings = Settings::default();
+    settings.parse_binds = true;
+    settings.evaluate_cfg = Some(evaluate_cfg);
+    assert!(crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[cfg(feature = "a")]
+                #[task(binds = UART0)]
+                fn foo(_: foo::Context) {}
+
+                #[cfg(feature = "a")]
+                #[task(binds = UART0)]
+                fn bar(_: bar::Context) {}
+            }
+        ),
+        settings,
+    )
+    .is_err());
+}
+
+#[test]
+fn reserved_priorities() {
+    let app = quote!(
+        mod app {
+            #[task]
+            fn foo(_: foo::Context) {}
+
+            #[task(priority = 5)]
+            fn bar(_: bar::Context) {}
+        }
+    );
+
+    let mut settings = Settings::default();
+    settings.reserved_priorities.insert(1);
+    assert!(crate::parse2(quote!(dispatchers = [A, B]), app.clone(), settings).is_err());
+
+    // compressed priorities skip the reserved levels
+    let mut settings = Settings::default();
+    settings.optimize_priorities = true;
+    settings.reserved_priorities.insert(2);
+    let (parsed, analysis) = crate::parse2(quote!(dispatchers = [A, B]), app, settings).unwrap();
+
+    assert_eq!(parsed.software_tasks[0].args.priority, 1);
+    assert_eq!(parsed.software_tasks[1].args.priority, 3);
+    assert_eq!(
+        analysis.dispatchers().keys().cloned().collect::<Vec<_>>(),
+        [1, 3]
+    );
+}
+
+#[test]
+fn send_type_origins() {
+    // tokens parsed from a string carry their source locations
+    let app: proc_macro2::TokenStream = "mod app {
+    #[resources]
+    struct Resources {
+        x: u32,
+    }
+
+    #[init]
+    fn init(_: init::Context) -> (init::LateResources, init::Monotonics) {}
+
+    #[task(resources = [x])]
+    fn foo(_: foo::Context, _: u32) {}
+}"
+    .parse()
+    .unwrap();
+
+    let (app, analysis) =
+        crate::parse2(quote!(dispatchers = [A]), app, Settings::default()).unwrap();
+
+    assert_eq!(analysis.send_types.len(), 1);
+    let ty = analysis.send_types.iter().next().unwrap();
+    let origins = analysis.send_type_origins(ty);
+    assert_eq!(
+        origins,
+        [
+            TypeOrigin::Resource(quote::format_ident!("x")),
+            TypeOrigin::TaskInput {
+                task: quote::format_ident!("foo"),
+                index: 0,
+            },
+        ]
+    );
+
+    // the field's type in the `#[resources]` struct
+    let start = origins[0].span(&app).start();
+    assert_eq!((start.line, start.column), (4, 11));
+
+    // the input's type in the task signature
+    let start = origins[1].span(&app).start();
+    assert_eq!((start.line, start.column), (11, 31));
+
+    assert!(analysis.sync_type_origins(ty).is_empty());
+}
+
+#[test]
+fn shares_line_with() {
+    let mut settings = Settings::default();
+    settings.parse_binds = true;
+    let (app, analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[task(binds = UART0, shares_line_with = [UART2, UART4])]
+                fn foo(_: foo::Context) {}
+
+                #[task(binds = UART1)]
+                fn bar(_: bar::Context) {}
+            }
+        ),
+        settings,
+    )
+    .unwrap();
+
+    let foo = &app.hardware_tasks[0].args;
+    assert_eq!(
+        foo.shares_line_with.iter().cloned().collect::<Vec<_>>(),
+        [quote::format_ident!("UART2"), quote::format_ident!("UART4")]
+    );
+    assert_eq!(foo.arg_order[1], "shares_line_with");
+
+    assert_eq!(analysis.interrupts.len(), 2);
+    let (interrupt, uart0) = analysis.interrupts.get_index(0).unwrap();
+    assert_eq!(interrupt, "UART0");
+    assert_eq!(uart0.tasks[0].0, "foo");
+    assert_eq!(
+        uart0.shares_line_with,
+        foo.shares_line_with.iter().cloned().collect::<Vec<_>>()
+    );
+    let (interrupt, uart1) = analysis.interrupts.get_index(1).unwrap();
+    assert_eq!(interrupt, "UART1");
+    assert!(uart1.shares_line_with.is_empty());
+
+    // sharing a line doesn't allow binding the same interrupt twice
+    let mut settings = Settings::default();
+    settings.parse_binds = true;
+    assert_eq!(
+        crate::parse2(
+            quote!(),
+            quote!(
+                mod app {
+                    #[task(binds = UART0, shares_line_with = [UART2])]
+                    fn foo(_: foo::Context) {}
+
+                    #[task(binds = UART0, shares_line_with = [UART2])]
+                    fn bar(_: bar::Context) {}
+                }
+            ),
+            settings,
+        )
+        .unwrap_err()
+        .to_string(),
+        "this interrupt is already bound"
+    );
+
+    // software tasks are not bound to interrupts
+    let mut settings = Settings::default();
+    settings.parse_binds = true;
+    assert!(crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[task(shares_line_with = [UART2])]
+                fn foo(_: foo::Context) {}
+            }
+        ),
+        settings,
+    )
+    .is_err());
+}
+
+#[test]
+fn elide_trivial_trait_asserts() {
+    let app = quote!(
+        mod app {
+            #[resources]
+            struct Resources {
+                #[init(foo)]
+                handler: fn(u8),
+                #[init(Frame)]
+                frame: Frame,
+            }
+
+            #[task(binds = UART0, resources = [&handler, &frame])]
+            fn foo(_: foo::Context) {}
+
+            #[task(binds = UART1, priority = 2, resources = [&handler, &frame])]
+            fn bar(_: bar::Context) {}
+        }
+    );
+
+    let mut settings = Settings::default();
+    settings.parse_binds = true;
+    let (_app, analysis) = crate::parse2(quote!(), app.clone(), settings).unwrap();
+    assert_eq!(analysis.sync_types.len(), 2);
+
+    // function pointers are always `Send` and `Sync`
+    let mut settings = Settings::default();
+    settings.parse_binds = true;
+    settings.elide_trivial_trait_asserts = true;
+    let (_app, analysis) = crate::parse2(quote!(), app, settings).unwrap();
+    let ty = analysis.sync_types.iter().next().unwrap();
+    assert_eq!(analysis.sync_types.len(), 1);
+    assert_eq!(quote!(#ty).to_string(), "Frame");
+}
+
+#[test]
+fn latency_hints() {
+    let app = quote!(
+        mod app {
+            #[task(binds = EXTI0, priority = 3, latency = 50)]
+            fn foo(_: foo::Context) {}
+
+            #[task(latency = 100)]
+            fn bar(_: bar::Context) {}
+
+            #[task(priority = 2, latency = 200)]
+            fn baz(_: baz::Context) {}
+
+            #[task(priority = 2)]
+            fn qux(_: qux::Context) {}
+        }
+    );
+
+    let mut settings = Settings::default();
+    settings.parse_binds = true;
+    assert_eq!(
+        crate::parse2(quote!(dispatchers = [A, B]), app.clone(), settings)
+            .unwrap_err()
+            .to_string(),
+        "unexpected argument"
+    );
+
+    let mut settings = Settings::default();
+    settings.parse_binds = true;
+    settings.parse_latency_hints = true;
+    let (app, analysis) = crate::parse2(quote!(dispatchers = [A, B]), app, settings).unwrap();
+
+    assert_eq!(app.hardware_tasks[0].args.latency, Some(50));
+    assert_eq!(app.software_tasks[2].args.latency, None);
+
+    let hints = analysis
+        .latency_hints
+        .iter()
+        .map(|hint| (hint.task.to_string(), hint.priority, hint.latency))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        hints,
+        [
+            ("bar".to_string(), 1, 100),
+            ("baz".to_string(), 2, 200),
+            ("foo".to_string(), 3, 50),
+        ]
+    );
+
+    // `baz` has a higher priority than `bar` but a looser budget
+    let inversions = analysis.latency_inversions();
+    assert_eq!(inversions.len(), 1);
+    assert_eq!(inversions[0].0.task, "bar");
+    assert_eq!(inversions[0].1.task, "baz");
+}
+
+#[test]
+fn trait_object_resources() {
+    // trait objects behind a pointer are sized
+    let (app, _analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[resources]
+                struct Resources {
+                    #[init(None)]
+                    x: Option<Box<dyn core::fmt::Write>>,
+                    #[init(&Foo)]
+                    y: &'static dyn Send,
+                }
+            }
+        ),
+        Settings::default(),
+    )
+    .unwrap();
+
+    assert_eq!(app.resources.len(), 2);
+}
+
+#[test]
+fn interrupts_across_cfg() {
+    let app = crate::parse::app(
+        quote!(),
+        quote!(
+            mod app {
+                #[cfg(feature = "a")]
+                #[task(binds = UART0, shares_line_with = [UART2])]
+                fn foo(_: foo::Context) {}
+
+                #[cfg(not(feature = "a"))]
+                #[task(binds = UART0, priority = 2, shares_line_with = [UART2, UART4])]
+                fn bar(_: bar::Context) {}
+            }
+        ),
+        &mut Settings {
+            parse_binds: true,
+            ..Settings::default()
+        },
+    )
+    .unwrap();
+    let mut analysis = crate::analyze::app(&app, &Settings::default()).unwrap();
+
+    // one entry that holds both tasks
+    assert_eq!(analysis.interrupts.len(), 1);
+    let uart0 = &analysis.interrupts[0];
+    assert_eq!(uart0.tasks.len(), 2);
+    assert_eq!(uart0.tasks[0].0, "foo");
+    assert_eq!(uart0.tasks[0].1.len(), 1);
+    assert_eq!(uart0.tasks[1].0, "bar");
+    assert_eq!(uart0.tasks[1].1.len(), 1);
+    assert_eq!(uart0.shares_line_with, ["UART2", "UART4"]);
+    assert!(analysis.is_sound(&app).is_ok());
+
+    // tasks without `#[cfg]`s never bind the same interrupt
+    for (_, cfgs) in &mut analysis.interrupts.values_mut().next().unwrap().tasks {
+        cfgs.clear();
+    }
+    assert_eq!(analysis.is_sound(&app).err().unwrap().len(), 1);
+
+    // three-way split
+    fn evaluate_cfg(attr: &syn::Attribute) -> bool {
+        quote!(#attr).to_string().contains("\"a\"")
+    }
+
+    let mut settings = Settings::default();
+    settings.parse_binds = true;
+    settings.evaluate_cfg = Some(evaluate_cfg);
+    let (_app, analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[cfg(feature = "c")]
+                #[task(binds = UART0)]
+                fn baz(_: baz::Context) {}
+
+                #[cfg(feature = "a")]
+                #[task(binds = UART0)]
+                fn foo(_: foo::Context) {}
+
+                #[cfg(feature = "b")]
+                #[task(binds = UART0)]
+                fn bar(_: bar::Context) {}
+            }
+        ),
+        settings,
+    )
+    .unwrap();
+
+    let names = analysis.interrupts[0]
+        .tasks
+        .iter()
+        .map(|(name, _)| name.to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(names, ["baz", "foo", "bar"]);
+}
+
+#[test]
+fn data_race_candidates() {
+    let mut settings = Settings::default();
+    settings.parse_binds = true;
+    let (app, mut analysis) = crate::parse2(
+        quote!(),
+        quote!(
+            mod app {
+                #[resources]
+                struct Resources {
+                    #[init(0)]
+                    x: u32,
+                    #[init(0)]
+                    y: u32,
+                }
+
+                #[task(binds = UART0, resources = [x, &y])]
+                fn foo(_: foo::Context) {}
+
+                #[task(binds = UART1, priority = 2, resources = [x, &y])]
+                fn bar(_: bar::Context) {}
+            }
+        ),
+        settings,
+    )
+    .unwrap();
+
+    // `x` is contended; nobody writes `y`
+    assert!(analysis.data_race_candidates(&app).is_empty());
+
+    // a regression that forgets about `foo`
+    *analysis.ownerships_mut().values_mut().next().unwrap() = Ownership::Owned { priority: 2 };
+    let candidates = analysis.data_race_candidates(&app);
+    assert_eq!(candidates.len(), 1);
+    let (resource, contexts) = &candidates[0];
+    assert_eq!(resource, "x");
+    let contexts = contexts
+        .iter()
+        .map(|context| context.ident(&app).to_string())
+        .collect::<Vec<_>>();
+    assert_eq!(contexts, ["foo", "bar"]);
+}
+
+#[test]
+fn enforce_snake_case_tasks() {
+    let app = |resource: proc_macro2::TokenStream| {
+        quote!(
+            mod app {
+                #[resources]
+                struct Resources {
+                    #[init(0)]
+                    #resource: u32,
+                }
+
+                #[task(resources = [#resource])]
+                fn send_data_2(_: send_data_2::Context) {}
+            }
+        )
+    };
+
+    let settings = |enforce_snake_case_tasks| {
+        let mut settings = Settings::default();
+        settings.enforce_snake_case_tasks = enforce_snake_case_tasks;
+        settings
+    };
+
+    assert!(crate::parse2(
+        quote!(dispatchers = [A]),
+        app(quote!(_counter)),
+        settings(true)
+    )
+    .is_ok());
+
+    let err = crate::parse2(
+        quote!(dispatchers = [A]),
+        app(quote!(rxCounter)),
+        settings(true),
+    )
+    .err()
+    .unwrap();
+    assert_eq!(
+        err.to_string(),
+        "resource names must be `snake_case`; rename this resource to `rx_counter`"
+    );
+
+    // off by default
+    assert!(crate::parse2(
+        quote!(dispatchers = [A]),
+        app(quote!(rxCounter)),
+        settings(false)
+    )
+    .is_ok());
+}
+
+#[test]
+fn into_parts() {
+    let (app, _analysis) = crate::parse2(
+        quote!(dispatchers = [UART0, UART1]),
+        quote!(
+            mod app {
+                #[resources]
+                struct Resources {
+                    #[init(0)]
+                    x: u32,
+                }
+
+                #[task(resources = [x])]
+                fn foo(_: foo::Context) {}
+
+                #[task(priority = 2)]
+                fn bar(_: bar::Context) {}
+            }
+        ),
+        Settings::default(),
+    )
+    .unwrap();
+
+    let borrowed = app
+        .software_tasks
+        .keys()
+        .map(|name| name.to_string())
+        .collect::<Vec<_>>();
+
+    let parts = app.into_inner().into_parts();
+    let owned = parts
+        .software_tasks
+        .into_iter()
+        .map(|(name, _)| name.to_string())
+        .collect::<Vec<_>>();
+
+    assert_eq!(owned, borrowed);
+    assert_eq!(owned, ["foo", "bar"]);
+    assert_eq!(parts.name.to_string(), "app");
+    assert_eq!(parts.resources.len(), 1);
+}
@@ -0,0 +1,36 @@
+//! An application as a backend would see it in a user's crate
+
+#![no_main]
+#![no_std]
+
+#[rtic::app(dispatchers = [SSI0, SSI1])]
+mod app {
+    #[resources]
+    struct Resources {
+        #[init(0)]
+        counter: u32,
+        sensor: Sensor,
+    }
+
+    #[init]
+    fn init(_: init::Context) -> (init::LateResources, init::Monotonics) {
+        foo::spawn().unwrap();
+
+        (init::LateResources { sensor: Sensor }, init::Monotonics())
+    }
+
+    #[idle(resources = [&sensor])]
+    fn idle(_: idle::Context) -> ! {
+        loop {}
+    }
+
+    #[task(priority = 2, resources = [counter])]
+    fn foo(_: foo::Context) {
+        bar::spawn().unwrap();
+    }
+
+    #[task(resources = [counter, &sensor])]
+    fn bar(_: bar::Context) {}
+}
+
+pub struct Sensor;
@@ -0,0 +1,64 @@
+//! The parser and the analysis run outside a procedural macro, e.g. from a build script
+
+use std::{fs, path::Path};
+
+use proc_macro2::{TokenStream, TokenTree};
+use quote::quote;
+use rtic_syntax::{analyze::Ownership, Settings};
+use syn::Item;
+
+// Splits the `#[app]` module of `file` into the arguments of the attribute and the module itself
+fn app_module(file: &syn::File) -> (TokenStream, TokenStream) {
+    file.items
+        .iter()
+        .find_map(|item| match item {
+            Item::Mod(module) => {
+                let (apps, attrs): (Vec<_>, Vec<_>) =
+                    module.attrs.iter().cloned().partition(|attr| {
+                        attr.path
+                            .segments
+                            .last()
+                            .map_or(false, |s| s.ident == "app")
+                    });
+                let app = apps.into_iter().next()?;
+
+                let args = match app.tokens.into_iter().next() {
+                    Some(TokenTree::Group(group)) => group.stream(),
+                    _ => TokenStream::new(),
+                };
+
+                let mut module = module.clone();
+                module.attrs = attrs;
+                Some((args, quote!(#module)))
+            }
+            _ => None,
+        })
+        .expect("no `#[app]` module")
+}
+
+#[test]
+fn parse_file() {
+    let path = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/app.rs");
+    let file = syn::parse_file(&fs::read_to_string(path).unwrap()).unwrap();
+
+    let (args, input) = app_module(&file);
+    let (app, analysis) = rtic_syntax::parse2(args, input, Settings::default()).unwrap();
+
+    assert_eq!(app.name.to_string(), "app");
+    assert_eq!(
+        app.software_tasks
+            .keys()
+            .map(|task| task.to_string())
+            .collect::<Vec<_>>(),
+        ["foo", "bar"]
+    );
+    assert_eq!(analysis.late_resource_assignment.len(), 1);
+    assert_eq!(analysis.dispatchers().len(), 2);
+
+    let counter = analysis
+        .ownerships()
+        .iter()
+        .find(|(name, _)| *name == "counter")
+        .map(|(_, ownership)| ownership);
+    assert_eq!(counter, Some(&Ownership::Contended { ceiling: 2 }));
+}
@@ -0,0 +1,85 @@
+//! Backends should be able to get by importing only the prelude
+
+extern crate proc_macro;
+
+use quote::quote;
+use rtic_syntax::prelude::*;
+
+#[allow(dead_code)]
+fn touch_ast(app: &App) {
+    let _: &AppArgs = &app.args;
+    let _: Option<&Init> = app.inits.first();
+    let _: Option<&InitArgs> = app.inits.first().map(|init| &init.args);
+    let _: Option<&Idle> = app.idles.first();
+    let _: Option<&IdleArgs> = app.idles.first().map(|idle| &idle.args);
+    let _: &Map<Resource> = &app.resources;
+    let _: &Map<LateResource> = &app.late_resources;
+    let _: &Map<Monotonic> = &app.monotonics;
+    let _: Option<&MonotonicArgs> = app.monotonics.values().next().map(|mono| &mono.args);
+    let _: &Map<HardwareTask> = &app.hardware_tasks;
+    let _: Option<&HardwareTaskArgs> = app.hardware_tasks.values().next().map(|t| &t.args);
+    let _: &Map<SoftwareTask> = &app.software_tasks;
+    let _: Option<&SoftwareTaskArgs> = app.software_tasks.values().next().map(|t| &t.args);
+    let _: Option<&Map<Local>> = app.software_tasks.values().next().map(|t| &t.locals);
+    let _: Option<&Access> = app
+        .software_tasks
+        .values()
+        .next()
+        .and_then(|t| t.args.resources.values().next());
+    let _: Set<Priority> = app
+        .software_tasks
+        .values()
+        .map(|t| t.args.priority)
+        .collect();
+    let _: EffectivePriority = Context::Init.effective_priority(app);
+}
+
+#[allow(dead_code)]
+fn touch_analysis(analysis: &Analysis) {
+    let _: &Channels = analysis.channels();
+    let _: Option<&Channel> = analysis.channels().values().next();
+    let _: &Dispatchers = analysis.dispatchers();
+    let _: &ContextLayouts = &analysis.context_layouts;
+    let _: Option<&ContextLayout> = analysis.context_layouts.values().next();
+    let _: CsStrategy = analysis.critical_section_strategy;
+    let _: &LateResources = &analysis.late_resource_assignment;
+    let _: &Locations = &analysis.locations;
+    let _: Option<&Location> = analysis.locations.values().next();
+    let _: &Ownerships = analysis.ownerships();
+    let _: Option<&Ownership> = analysis.ownerships().values().next();
+    let _: &PriorityUsage = analysis.priority_usage();
+    let _: Option<&PriorityUse> = analysis.priority_usage().values().next();
+    let _: &ResourceUsers = &analysis.resource_users;
+    let _: Option<&ResourceUser> = analysis.resource_users.values().flatten().next();
+    let _: &SendTypes = &analysis.send_types;
+    let _: &SyncTypes = &analysis.sync_types;
+}
+
+#[allow(dead_code)]
+fn touch_entry_points(args: proc_macro::TokenStream, input: proc_macro::TokenStream) {
+    let _ = parse(args, input, Settings::default());
+}
+
+#[test]
+fn prelude() {
+    let mut settings = Settings::default();
+    settings.init_return_type = InitReturnKind::LateResources;
+
+    let (app, analysis): (P<App>, P<Analysis>) = parse2(
+        quote!(dispatchers = [A]),
+        quote!(
+            mod app {
+                #[init]
+                fn init(_: init::Context) -> init::LateResources {}
+
+                #[task]
+                fn foo(_: foo::Context) {}
+            }
+        ),
+        settings,
+    )
+    .unwrap();
+
+    touch_ast(&app);
+    touch_analysis(&analysis);
+}
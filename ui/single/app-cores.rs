@@ -0,0 +1,4 @@
+#![no_main]
+
+#[mock::app(cores = 2)]
+mod app {}
@@ -0,0 +1,4 @@
+#![no_main]
+
+#[mock::app(device = stm32f4::stm32f407, device = lm3s6965)]
+mod app {}
@@ -0,0 +1,4 @@
+#![no_main]
+
+#[mock::app(peripherals = true)]
+mod app {}
@@ -0,0 +1,7 @@
+#![no_main]
+
+#[mock::app_strict(parse_binds)]
+mod app {
+    #[task(binds = UART0)]
+    fn foo(_: foo::Context) {}
+}
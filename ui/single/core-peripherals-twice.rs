@@ -0,0 +1,12 @@
+#![no_main]
+
+#[mock::app_core_peripherals]
+mod app {
+    #[init(core_peripherals = true)]
+    fn init(_: init::Context) -> (init::LateResources, init::Monotonics) {}
+
+    #[idle(core_peripherals = true)]
+    fn idle(_: idle::Context) -> ! {
+        loop {}
+    }
+}
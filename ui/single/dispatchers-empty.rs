@@ -0,0 +1,7 @@
+#![no_main]
+
+#[mock::app(dispatchers = [])]
+mod app {
+    #[task]
+    fn foo(_: foo::Context) {}
+}
@@ -0,0 +1,9 @@
+#![no_main]
+
+#[mock::app]
+mod app {
+    #[idle]
+    extern "C" fn idle(_: idle::Context) -> ! {
+        loop {}
+    }
+}
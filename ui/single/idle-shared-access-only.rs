@@ -0,0 +1,15 @@
+#![no_main]
+
+#[mock::app_idle_shared]
+mod app {
+    #[resources]
+    struct Resources {
+        #[init(0)]
+        x: u32,
+    }
+
+    #[idle(resources = [x])]
+    fn idle(_: idle::Context) -> ! {
+        loop {}
+    }
+}
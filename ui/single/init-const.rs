@@ -0,0 +1,7 @@
+#![no_main]
+
+#[mock::app]
+mod app {
+    #[init]
+    const fn init(_: init::Context) -> (init::LateResources, init::Monotonics) {}
+}
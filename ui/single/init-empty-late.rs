@@ -0,0 +1,12 @@
+#![no_main]
+
+#[mock::app]
+mod app {
+    #[resources]
+    struct Resources {
+        x: u32,
+    }
+
+    #[init(late = [])]
+    fn init(_: init::Context) -> (init::LateResources, init::Monotonics) {}
+}
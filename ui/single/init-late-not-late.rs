@@ -0,0 +1,17 @@
+#![no_main]
+
+#[mock::app]
+mod app {
+    #[resources]
+    struct Resources {
+        a: u32,
+        #[init(0)]
+        b: u32,
+    }
+
+    #[init(late = [a, b])]
+    fn init(_: init::Context) -> (init::LateResources, init::Monotonics) {}
+
+    #[task(resources = [a, b])]
+    fn foo(_: foo::Context) {}
+}
@@ -0,0 +1,16 @@
+#![no_main]
+
+#[mock::app]
+mod app {
+    #[resources]
+    struct Resources {
+        a: u32,
+        b: u32,
+    }
+
+    #[init(late = [a])]
+    fn init(_: init::Context) -> (init::LateResources, init::Monotonics) {}
+
+    #[task(resources = [a, b])]
+    fn foo(_: foo::Context) {}
+}
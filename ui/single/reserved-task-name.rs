@@ -0,0 +1,7 @@
+#![no_main]
+
+#[mock::app]
+mod app {
+    #[task]
+    fn LateResources(_: LateResources::Context) {}
+}
@@ -0,0 +1,9 @@
+#![no_main]
+
+#[mock::app]
+mod app {
+    #[resources]
+    struct Resources {
+        x: dyn core::fmt::Write,
+    }
+}
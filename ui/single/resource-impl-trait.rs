@@ -0,0 +1,10 @@
+#![no_main]
+
+#[mock::app]
+mod app {
+    #[resources]
+    struct Resources {
+        #[init(0)]
+        x: impl Copy,
+    }
+}
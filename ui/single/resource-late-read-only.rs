@@ -0,0 +1,15 @@
+#![no_main]
+
+#[mock::app_read_only_late]
+mod app {
+    #[resources]
+    struct Resources {
+        x: u32,
+    }
+
+    #[init]
+    fn init(_: init::Context) -> (init::LateResources, init::Monotonics) {}
+
+    #[task(resources = [&x])]
+    fn foo(_: foo::Context) {}
+}
@@ -0,0 +1,19 @@
+#![no_main]
+
+#[mock::app]
+mod app {
+    #[resources]
+    struct Resources {
+        #[task_local]
+        #[init(0)]
+        x: u32,
+    }
+
+    #[idle(resources = [x])]
+    fn idle(_: idle::Context) -> ! {
+        loop {}
+    }
+
+    #[task(resources = [x])]
+    fn foo(_: foo::Context) {}
+}
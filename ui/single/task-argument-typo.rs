@@ -0,0 +1,7 @@
+#![no_main]
+
+#[mock::app(parse_binds)]
+mod app {
+    #[task(binds = UART0, priority = 2, resorces = [], capacity = 1, prio = 3)]
+    fn foo(_: foo::Context) {}
+}
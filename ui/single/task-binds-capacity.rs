@@ -0,0 +1,7 @@
+#![no_main]
+
+#[mock::app(parse_binds)]
+mod app {
+    #[task(binds = UART0, capacity = 4)]
+    fn foo(_: foo::Context) {}
+}
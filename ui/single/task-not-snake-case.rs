@@ -0,0 +1,7 @@
+#![no_main]
+
+#[mock::app_snake_case(dispatchers = [UART0])]
+mod app {
+    #[task]
+    fn SendData(_: SendData::Context) {}
+}
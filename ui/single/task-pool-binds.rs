@@ -0,0 +1,13 @@
+#![no_main]
+
+#[mock::app(parse_binds)]
+mod app {
+    #[resources]
+    struct Resources {
+        #[init(Pool::new())]
+        p: Pool,
+    }
+
+    #[task(binds = UART0, pool = p)]
+    fn foo(_: foo::Context) {}
+}
@@ -0,0 +1,15 @@
+#![no_main]
+
+#[mock::app]
+mod app {
+    #[resources]
+    struct Resources {
+        p: Pool,
+    }
+
+    #[init]
+    fn init(_: init::Context) -> (init::LateResources, init::Monotonics) {}
+
+    #[task(pool = p)]
+    fn foo(_: foo::Context) {}
+}
@@ -0,0 +1,7 @@
+#![no_main]
+
+#[mock::app]
+mod app {
+    #[task(pool = P)]
+    fn foo(_: foo::Context) {}
+}
@@ -0,0 +1,10 @@
+#![no_main]
+
+#[mock::app_reserved(parse_binds)]
+mod app {
+    #[task(binds = UART0)]
+    fn foo(_: foo::Context) {}
+
+    #[task(binds = UART1, priority = 2)]
+    fn bar(_: bar::Context) {}
+}
@@ -0,0 +1,7 @@
+#![no_main]
+
+#[mock::app]
+mod app {
+    #[task]
+    extern "C" fn foo(_: foo::Context) {}
+}
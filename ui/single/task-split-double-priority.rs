@@ -0,0 +1,8 @@
+#![no_main]
+
+#[mock::app]
+mod app {
+    #[task(priority = 1)]
+    #[task(capacity = 2, priority = 2)]
+    fn foo(_: foo::Context) {}
+}